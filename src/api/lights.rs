@@ -1,5 +1,6 @@
 use crate::constants::SHADOW_FORMAT;
-use crate::{Camera, WgpuRenderer};
+use crate::slots::SlotKey;
+use crate::{Camera, RendererError, WgpuRenderer};
 use std::num::NonZeroU32;
 
 pub struct RealLightPublic {
@@ -23,6 +24,22 @@ pub struct RealLight {
     pub quadratic: f32,
     pub active: bool,
     pub resolution: [u32; 2],
+    /// counts how often the light was removed, handles from before that don't point to it anymore
+    pub(crate) generation: u32,
+}
+
+/// points at a real light
+/// the shadow texture layer of a removed light is reused, handles to the removed light stay invalid
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RealLightHandle {
+    key: SlotKey,
+}
+impl RealLightHandle {
+    pub(crate) fn new(index: usize, generation: u32) -> Self {
+        Self {
+            key: SlotKey { index, generation },
+        }
+    }
 }
 
 #[repr(C)]
@@ -112,16 +129,42 @@ impl Default for LightAttenuation {
 }
 
 pub trait RealLightApi {
-    fn add_real_light(&mut self, light: RealLightPublic) -> Result<usize, String>;
-    fn remove_real_light(&mut self, light: usize);
-    fn get_real_light(&self, light: usize) -> Option<RealLightPublic>;
-    fn set_real_light(&mut self, id: usize, light: RealLightPublic);
+    fn add_real_light(&mut self, light: RealLightPublic) -> Result<RealLightHandle, RendererError>;
+    fn remove_real_light(&mut self, light: &RealLightHandle);
+    fn get_real_light(&self, light: &RealLightHandle) -> Option<RealLightPublic>;
+    fn set_real_light(
+        &mut self,
+        light: &RealLightHandle,
+        v: RealLightPublic,
+    ) -> Result<(), RendererError>;
     fn update_real_lights(&mut self);
     fn set_shadow_resolution(&mut self, res: [u32; 2]);
 }
 
+/// the view a light draws its shadow map into, every light has its own layer of the shadow texture
+fn shadow_layer_view(shadow_texture: &wgpu::Texture, layer: usize) -> wgpu::TextureView {
+    shadow_texture.create_view(&wgpu::TextureViewDescriptor {
+        label: Some("shadow"),
+        format: None,
+        dimension: Some(wgpu::TextureViewDimension::D2),
+        aspect: wgpu::TextureAspect::All,
+        base_mip_level: 0,
+        level_count: None,
+        base_array_layer: layer as u32,
+        array_layer_count: NonZeroU32::new(1),
+    })
+}
+
+impl WgpuRenderer {
+    fn real_light_mut(&mut self, light: &RealLightHandle) -> Option<&mut RealLight> {
+        self.real_lights
+            .get_mut(light.key.index)
+            .filter(|l| l.active && l.generation == light.key.generation)
+    }
+}
+
 impl RealLightApi for WgpuRenderer {
-    fn add_real_light(&mut self, light: RealLightPublic) -> Result<usize, String> {
+    fn add_real_light(&mut self, light: RealLightPublic) -> Result<RealLightHandle, RendererError> {
         if self.real_lights.iter().fold(0, |mut i, l| {
             if l.active {
                 i += 1
@@ -129,11 +172,10 @@ impl RealLightApi for WgpuRenderer {
             i
         }) >= self.max_real_lights
         {
-            Err(format!(
-                "maximum number of lights reached ({})",
-                self.max_real_lights
-            ))
+            Err(RendererError::TooManyLights(self.max_real_lights))
         } else {
+            // the new light has to be written to the storage buffer and get a shadow pass
+            self.lights_are_dirty = true;
             let mut reusing = None;
             let mut overwrite = None;
             for (i, l) in self.real_lights.iter_mut().enumerate() {
@@ -149,6 +191,7 @@ impl RealLightApi for WgpuRenderer {
                     } else {
                         reusing = Some(i);
                     }
+                    break;
                 }
             }
             if let Some(index) = reusing {
@@ -159,7 +202,10 @@ impl RealLightApi for WgpuRenderer {
                 self.real_lights[index].linear = light.attenuation.linear;
                 self.real_lights[index].quadratic = light.attenuation.quadratic;
                 self.real_lights[index].active = true;
-                Ok(index)
+                Ok(RealLightHandle::new(
+                    index,
+                    self.real_lights[index].generation,
+                ))
             } else if let Some(index) = overwrite {
                 self.real_lights[index].resolution = self.shadow_resolution;
                 self.real_lights[index].camera = light.camera;
@@ -169,56 +215,43 @@ impl RealLightApi for WgpuRenderer {
                 self.real_lights[index].quadratic = light.attenuation.quadratic;
                 self.real_lights[index].active = true;
                 self.real_lights[index].target_view =
-                    self.shadow_texture
-                        .create_view(&wgpu::TextureViewDescriptor {
-                            label: Some("shadow"),
-                            format: None,
-                            dimension: Some(wgpu::TextureViewDimension::D2),
-                            aspect: wgpu::TextureAspect::All,
-                            base_mip_level: 0,
-                            level_count: None,
-                            base_array_layer: index as u32,
-                            array_layer_count: NonZeroU32::new(1),
-                        });
-                Ok(index)
+                    shadow_layer_view(&self.shadow_texture, index);
+                Ok(RealLightHandle::new(
+                    index,
+                    self.real_lights[index].generation,
+                ))
             } else {
-                let attenuation = LightAttenuation::default();
+                let attenuation = light.attenuation;
                 self.real_lights.push(RealLight {
                     camera: light.camera,
                     color: light.color,
-                    target_view: self
-                        .shadow_texture
-                        .create_view(&wgpu::TextureViewDescriptor {
-                            label: Some("shadow"),
-                            format: None,
-                            dimension: Some(wgpu::TextureViewDimension::D2),
-                            aspect: wgpu::TextureAspect::All,
-                            base_mip_level: 0,
-                            level_count: None,
-                            base_array_layer: self.real_lights.len() as u32,
-                            array_layer_count: NonZeroU32::new(1),
-                        }),
+                    target_view: shadow_layer_view(&self.shadow_texture, self.real_lights.len()),
                     default: light.default,
                     constant: attenuation.constant,
                     linear: attenuation.linear,
                     quadratic: attenuation.quadratic,
                     active: true,
                     resolution: self.shadow_resolution,
+                    generation: 0,
                 });
-                Ok(self.real_lights.len() - 1)
+                Ok(RealLightHandle::new(self.real_lights.len() - 1, 0))
             }
         }
     }
-    fn remove_real_light(&mut self, light: usize) {
-        self.real_lights[light].active = false;
-    }
-    fn get_real_light(&self, light: usize) -> Option<RealLightPublic> {
-        if light >= self.real_lights.len() {
-            return None;
+    /// remove a real light, a handle to a light that was already removed is ignored
+    fn remove_real_light(&mut self, light: &RealLightHandle) {
+        if let Some(light) = self.real_light_mut(light) {
+            light.active = false;
+            light.generation = light.generation.wrapping_add(1);
+            self.lights_are_dirty = true;
         }
-        let light = &self.real_lights[light];
-        if light.active {
-            Some(RealLightPublic {
+    }
+    /// get a copy of a real light, None if it was removed
+    fn get_real_light(&self, light: &RealLightHandle) -> Option<RealLightPublic> {
+        self.real_lights
+            .get(light.key.index)
+            .filter(|l| l.active && l.generation == light.key.generation)
+            .map(|light| RealLightPublic {
                 camera: light.camera,
                 color: light.color,
                 default: light.default,
@@ -228,19 +261,20 @@ impl RealLightApi for WgpuRenderer {
                     quadratic: light.quadratic,
                 },
             })
-        } else {
-            None
-        }
     }
-    fn set_real_light(&mut self, light: usize, v: RealLightPublic) {
-        if light < self.real_lights.len() {
-            let light = &mut self.real_lights[light];
-            if light.active {
-                light.default = v.default;
-                light.color = v.color;
-                light.camera = v.camera;
-            }
-        }
+    /// change a real light, fails if it was removed
+    fn set_real_light(
+        &mut self,
+        light: &RealLightHandle,
+        v: RealLightPublic,
+    ) -> Result<(), RendererError> {
+        let light = self
+            .real_light_mut(light)
+            .ok_or(RendererError::InvalidHandle)?;
+        light.default = v.default;
+        light.color = v.color;
+        light.camera = v.camera;
+        Ok(())
     }
     fn update_real_lights(&mut self) {
         self.lights_are_dirty = true;
@@ -265,11 +299,11 @@ impl RealLightApi for WgpuRenderer {
             .shadow_texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
-        for i in 0..self.real_lights.len() {
-            if let Some(light) = self.get_real_light(i) {
-                self.remove_real_light(i);
-                self.add_real_light(light).unwrap();
-            }
+        // the lights keep their layers, so the handles to them stay valid
+        for (index, light) in self.real_lights.iter_mut().enumerate() {
+            light.target_view = shadow_layer_view(&self.shadow_texture, index);
+            light.resolution = res;
         }
+        self.lights_are_dirty = true;
     }
 }
@@ -1,51 +1,77 @@
 use crate::color_mesh::ColorMesh;
 use crate::model::Model;
+use crate::slots::SlotKey;
 use crate::uv_mesh::UvModel;
-use crate::{ColorMeshInstance, ColorVertex, Line, Transform, UvVertex, WgpuRenderer};
+use crate::{
+    BlendMode, ColorMeshInstance, ColorVertex, Indices, Instances, Line, RenderState,
+    RendererError, Transform, UvVertex, WgpuRenderer,
+};
 use std::path::{Path, PathBuf};
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ColorMeshHandle {
-    index: usize,
+    key: SlotKey,
 }
 impl ColorMeshHandle {
-    pub fn new(index: usize) -> Self {
-        Self { index }
+    pub(crate) fn new(key: SlotKey) -> Self {
+        Self { key }
+    }
+    pub(crate) fn key(&self) -> SlotKey {
+        self.key
     }
 }
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct UvMeshHandle {
-    index: usize,
+    key: SlotKey,
 }
 impl UvMeshHandle {
-    pub fn new(index: usize) -> Self {
-        Self { index }
+    pub(crate) fn new(key: SlotKey) -> Self {
+        Self { key }
+    }
+    pub(crate) fn key(&self) -> SlotKey {
+        self.key
     }
 }
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ModelHandle {
-    index: usize,
+    key: SlotKey,
 }
 impl ModelHandle {
-    pub fn new(index: usize) -> Self {
-        Self { index }
+    pub(crate) fn new(key: SlotKey) -> Self {
+        Self { key }
+    }
+    pub(crate) fn key(&self) -> SlotKey {
+        self.key
     }
 }
 
 #[rustfmt::skip]
 pub trait MeshApi {
-    fn load_color_mesh(&mut self, vertices: Vec<ColorVertex>, indices: Option<Vec<u16>>) -> ColorMeshHandle;
+    fn load_color_mesh(&mut self, vertices: Vec<ColorVertex>, indices: Option<Indices>) -> ColorMeshHandle;
     fn remove_color_mesh(&mut self, mesh: &ColorMeshHandle);
-    fn color_mesh_instances(&mut self, mesh: &ColorMeshHandle) -> &mut Vec<ColorMeshInstance>;
+    fn color_mesh_instances(&mut self, mesh: &ColorMeshHandle) -> Result<&mut Instances<ColorMeshInstance>, RendererError>;
     fn update_color_mesh(&mut self, mesh: &ColorMeshHandle);
+    fn update_color_mesh_geometry(&mut self, mesh: &ColorMeshHandle, vertices: Vec<ColorVertex>, indices: Option<Indices>) -> Result<(), RendererError>;
+    fn set_color_mesh_layers(&mut self, mesh: &ColorMeshHandle, layers: u32) -> Result<(), RendererError>;
+    fn set_color_mesh_blend_mode(&mut self, mesh: &ColorMeshHandle, blend_mode: BlendMode) -> Result<(), RendererError>;
+    fn set_color_mesh_render_state(&mut self, mesh: &ColorMeshHandle, render_state: RenderState) -> Result<(), RendererError>;
 
-    fn load_uv_mesh<P: AsRef<Path>>(&mut self, vertices: Vec<UvVertex>, indices: Option<Vec<u16>>, texture: P) -> UvMeshHandle;
+    fn load_uv_mesh<P: AsRef<Path>>(&mut self, vertices: Vec<UvVertex>, indices: Option<Indices>, texture: P) -> Result<UvMeshHandle, RendererError>;
     fn remove_uv_mesh(&mut self, mesh: &UvMeshHandle);
-    fn uv_mesh_instances(&mut self, mesh: &UvMeshHandle) -> &mut Vec<Transform>;
+    fn uv_mesh_instances(&mut self, mesh: &UvMeshHandle) -> Result<&mut Instances<Transform>, RendererError>;
     fn update_uv_mesh(&mut self, mesh: &UvMeshHandle);
+    fn update_uv_mesh_geometry(&mut self, mesh: &UvMeshHandle, vertices: Vec<UvVertex>, indices: Option<Indices>) -> Result<(), RendererError>;
     fn write_raw_texture_to_uv_mesh(&mut self, mesh: &UvMeshHandle, size: (u32, u32), data: &[u8]);
+    fn set_uv_mesh_layers(&mut self, mesh: &UvMeshHandle, layers: u32) -> Result<(), RendererError>;
+    fn set_uv_mesh_blend_mode(&mut self, mesh: &UvMeshHandle, blend_mode: BlendMode) -> Result<(), RendererError>;
+    fn set_uv_mesh_render_state(&mut self, mesh: &UvMeshHandle, render_state: RenderState) -> Result<(), RendererError>;
 
-    fn load_model<P: AsRef<Path>>(&mut self, path: P) -> ModelHandle where PathBuf: std::convert::From<P>;
+    fn load_model<P: AsRef<Path>>(&mut self, path: P) -> Result<ModelHandle, RendererError> where PathBuf: std::convert::From<P>;
     fn remove_model(&mut self, model: ModelHandle);
-    fn model_instances(&mut self, model: &ModelHandle) -> Option<&mut Vec<Transform>>;
+    fn model_instances(&mut self, model: &ModelHandle) -> Result<&mut Instances<Transform>, RendererError>;
     fn update_model(&mut self, model: &ModelHandle);
+    fn set_model_layers(&mut self, model: &ModelHandle, layers: u32) -> Result<(), RendererError>;
+    fn set_model_render_state(&mut self, model: &ModelHandle, render_state: RenderState) -> Result<(), RendererError>;
 
     fn lines(&mut self) -> &mut Vec<Line>;
 }
@@ -54,125 +80,254 @@ impl MeshApi for WgpuRenderer {
     fn load_color_mesh(
         &mut self,
         vertices: Vec<ColorVertex>,
-        indices: Option<Vec<u16>>,
+        indices: Option<Indices>,
     ) -> ColorMeshHandle {
-        ColorMeshHandle::new(put_in_first_slot(
-            &mut self.color_meshes,
-            ColorMesh::from_vertices_and_indices(&self.device, vertices, indices),
-        ))
+        let mesh = ColorMesh::from_vertices_and_indices(&self.device, vertices, indices);
+        let key = self.color_meshes.insert(mesh);
+        self.prepare_color_mesh_pipeline(key);
+        ColorMeshHandle::new(key)
     }
+    /// remove a ColorMesh, a handle to a mesh that was already removed is ignored
     fn remove_color_mesh(&mut self, mesh: &ColorMeshHandle) {
-        self.color_meshes[mesh.index] = None;
+        self.color_meshes.remove(mesh.key);
     }
+    /// remove a UvMesh, a handle to a mesh that was already removed is ignored
     fn remove_uv_mesh(&mut self, mesh: &UvMeshHandle) {
-        self.uv_meshes[mesh.index] = None;
+        self.uv_meshes.remove(mesh.key);
     }
     /// get all the instance of a ColorMesh
-    fn color_mesh_instances(&mut self, mesh: &ColorMeshHandle) -> &mut Vec<ColorMeshInstance> {
-        if let Some(mesh) = &mut self.color_meshes[mesh.index] {
-            &mut mesh.instances
-        } else {
-            panic!("ColorMesh does not exist")
-        }
+    /// fails if the mesh was removed
+    fn color_mesh_instances(
+        &mut self,
+        mesh: &ColorMeshHandle,
+    ) -> Result<&mut Instances<ColorMeshInstance>, RendererError> {
+        self.color_meshes
+            .get_mut(mesh.key)
+            .map(|mesh| &mut mesh.instances)
+            .ok_or(RendererError::InvalidHandle)
     }
     /// load a model from a obj
     /// this is not working well, only simple models work properly
-    fn load_model<P: AsRef<Path>>(&mut self, path: P) -> ModelHandle
+    /// fails if the file can't be loaded or its extension is not glb or obj
+    fn load_model<P: AsRef<Path>>(&mut self, path: P) -> Result<ModelHandle, RendererError>
     where
         PathBuf: std::convert::From<P>,
     {
         let path: PathBuf = path.into();
-        let ext = dbg!(path.extension().unwrap());
-
-        if ext == "glb" {
-            ModelHandle::new(put_in_first_slot(
-                &mut self.models,
-                Model::load_gltf(&self.device, &self.queue, path),
-            ))
-        } else if ext == "obj" {
-            ModelHandle::new(put_in_first_slot(
-                &mut self.models,
-                Model::load(&self.device, &self.queue, path),
-            ))
-        } else {
-            panic!("format not supported");
-        }
+        let model = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("glb") => Model::load_gltf(&self.device, &self.queue, &path)?,
+            Some("obj") => Model::load(&self.device, &self.queue, &path)?,
+            _ => return Err(RendererError::UnsupportedFormat(path)),
+        };
+        let key = self.models.insert(model);
+        self.prepare_model_pipeline(key);
+        Ok(ModelHandle::new(key))
     }
-    fn model_instances(&mut self, model: &ModelHandle) -> Option<&mut Vec<Transform>> {
-        self.models[model.index]
-            .as_mut()
+    /// get all the instances of a Model
+    /// fails if the model was removed
+    fn model_instances(
+        &mut self,
+        model: &ModelHandle,
+    ) -> Result<&mut Instances<Transform>, RendererError> {
+        self.models
+            .get_mut(model.key)
             .map(|model| &mut model.instances)
+            .ok_or(RendererError::InvalidHandle)
     }
     /// update the instances of a Model
     /// this has to be called in order for any changes to take effect
+    /// only the instances that changed since the last update are written, so it is cheap to call every frame
     fn update_model(&mut self, model: &ModelHandle) {
-        if let Some(model) = &mut self.models[model.index] {
-            model.update(&self.device);
+        if let Some(model) = self.models.get_mut(model.key) {
+            model.update(&self.device, &self.queue);
         }
     }
     /// update the instances of a ColorMesh
     /// this has to be called in order for any changes to take effect
+    /// only the instances that changed since the last update are written, so it is cheap to call every frame
     fn update_color_mesh(&mut self, mesh: &ColorMeshHandle) {
-        if let Some(mesh) = &mut self.color_meshes[mesh.index] {
-            mesh.update(&self.device)
+        if let Some(mesh) = self.color_meshes.get_mut(mesh.key) {
+            mesh.update(&self.device, &self.queue);
         }
     }
+    /// replace the vertices and indices of a ColorMesh, the handle and the instances stay the same
+    /// the buffers of the mesh are reused while the new geometry fits into them
+    /// fails if the mesh was removed
+    fn update_color_mesh_geometry(
+        &mut self,
+        mesh: &ColorMeshHandle,
+        vertices: Vec<ColorVertex>,
+        indices: Option<Indices>,
+    ) -> Result<(), RendererError> {
+        self.color_meshes
+            .get_mut(mesh.key)
+            .ok_or(RendererError::InvalidHandle)?
+            .update_geometry(&self.device, &self.queue, &vertices, indices.as_ref());
+        // strips are restarted at the largest index, so the pipeline depends on the index format
+        self.prepare_color_mesh_pipeline(mesh.key);
+        Ok(())
+    }
     /// load a UvMesh
     /// UvMesh like ColorMesh but using a texture and uv coordinates instead of colors in the vertices
     fn load_uv_mesh<P: AsRef<Path>>(
         &mut self,
         vertices: Vec<UvVertex>,
-        indices: Option<Vec<u16>>,
+        indices: Option<Indices>,
         texture: P,
-    ) -> UvMeshHandle {
-        UvMeshHandle::new(put_in_first_slot(
-            &mut self.uv_meshes,
-            UvModel::new(vertices, indices, &self.device, &self.queue, texture),
-        ))
+    ) -> Result<UvMeshHandle, RendererError> {
+        let mesh = UvModel::new(vertices, indices, &self.device, &self.queue, texture)?;
+        let key = self.uv_meshes.insert(mesh);
+        self.prepare_uv_mesh_pipeline(key);
+        Ok(UvMeshHandle::new(key))
     }
     /// get all the instance of a UvMesh
-    fn uv_mesh_instances(&mut self, mesh: &UvMeshHandle) -> &mut Vec<Transform> {
-        if let Some(mesh) = &mut self.uv_meshes[mesh.index] {
-            &mut mesh.instances
-        } else {
-            panic!("UvMesh does not exist")
-        }
+    /// fails if the mesh was removed
+    fn uv_mesh_instances(
+        &mut self,
+        mesh: &UvMeshHandle,
+    ) -> Result<&mut Instances<Transform>, RendererError> {
+        self.uv_meshes
+            .get_mut(mesh.key)
+            .map(|mesh| &mut mesh.instances)
+            .ok_or(RendererError::InvalidHandle)
     }
     /// update the instances of a UVMesh
     /// this has to be called in order for any changes to take effect
+    /// only the instances that changed since the last update are written, so it is cheap to call every frame
     fn update_uv_mesh(&mut self, mesh: &UvMeshHandle) {
-        if let Some(uv_mesh) = &mut self.uv_meshes[mesh.index] {
-            uv_mesh.update(&self.device);
+        if let Some(uv_mesh) = self.uv_meshes.get_mut(mesh.key) {
+            uv_mesh.update(&self.device, &self.queue);
         }
     }
+    /// replace the vertices and indices of a UvMesh, the handle, the instances and the texture stay the same
+    /// the buffers of the mesh are reused while the new geometry fits into them
+    /// fails if the mesh was removed
+    fn update_uv_mesh_geometry(
+        &mut self,
+        mesh: &UvMeshHandle,
+        vertices: Vec<UvVertex>,
+        indices: Option<Indices>,
+    ) -> Result<(), RendererError> {
+        self.uv_meshes
+            .get_mut(mesh.key)
+            .ok_or(RendererError::InvalidHandle)?
+            .update_geometry(&self.device, &self.queue, &vertices, indices.as_ref());
+        self.prepare_uv_mesh_pipeline(mesh.key);
+        Ok(())
+    }
     /// Write a slice of bytes to the texture of a uv_mesh.
     /// When the size of the new texture is greater than the old one a new texture will have to be created. This is a bit slower.
     fn write_raw_texture_to_uv_mesh(&mut self, mesh: &UvMeshHandle, size: (u32, u32), data: &[u8]) {
-        if let Some(mesh) = self.uv_meshes[mesh.index].as_mut() {
+        if let Some(mesh) = self.uv_meshes.get_mut(mesh.key) {
+            // a mesh showing a render texture keeps showing it, the new texture is used once it is unbound
             if mesh
                 .diffuse_texture
                 .write_raw(&self.device, &self.queue, size, data)
+                && mesh.render_texture.is_none()
             {
                 mesh.update_texture(&self.device);
             }
         }
     }
+    /// remove a Model, a handle to a model that was already removed is ignored
     fn remove_model(&mut self, model: ModelHandle) {
-        self.models[model.index] = None;
+        self.models.remove(model.key);
+    }
+    /// set the viewport layers a ColorMesh is drawn in, it is only drawn by viewports whose layer mask includes one of them
+    /// the default is DEFAULT_LAYER
+    fn set_color_mesh_layers(
+        &mut self,
+        mesh: &ColorMeshHandle,
+        layers: u32,
+    ) -> Result<(), RendererError> {
+        self.color_meshes
+            .get_mut(mesh.key)
+            .map(|mesh| mesh.layers = layers)
+            .ok_or(RendererError::InvalidHandle)
+    }
+    /// set the viewport layers a UvMesh is drawn in
+    fn set_uv_mesh_layers(&mut self, mesh: &UvMeshHandle, layers: u32) -> Result<(), RendererError> {
+        self.uv_meshes
+            .get_mut(mesh.key)
+            .map(|mesh| mesh.layers = layers)
+            .ok_or(RendererError::InvalidHandle)
+    }
+    /// set how a ColorMesh is blended with what is behind it, the alpha of its colors is only used when it is not opaque
+    /// the default is BlendMode::Opaque
+    fn set_color_mesh_blend_mode(
+        &mut self,
+        mesh: &ColorMeshHandle,
+        blend_mode: BlendMode,
+    ) -> Result<(), RendererError> {
+        self.color_meshes
+            .get_mut(mesh.key)
+            .map(|mesh| mesh.blend_mode = blend_mode)
+            .ok_or(RendererError::InvalidHandle)?;
+        self.prepare_color_mesh_pipeline(mesh.key);
+        Ok(())
+    }
+    /// set how a UvMesh is blended with what is behind it, the alpha of its texture is only used when it is not opaque
+    fn set_uv_mesh_blend_mode(
+        &mut self,
+        mesh: &UvMeshHandle,
+        blend_mode: BlendMode,
+    ) -> Result<(), RendererError> {
+        self.uv_meshes
+            .get_mut(mesh.key)
+            .map(|mesh| mesh.blend_mode = blend_mode)
+            .ok_or(RendererError::InvalidHandle)?;
+        self.prepare_uv_mesh_pipeline(mesh.key);
+        Ok(())
+    }
+    /// set how the triangles of a ColorMesh are drawn, for example as a wireframe or from both sides
+    /// the pipeline for a new render state is created right away, it is shared by all meshes with the same state
+    fn set_color_mesh_render_state(
+        &mut self,
+        mesh: &ColorMeshHandle,
+        render_state: RenderState,
+    ) -> Result<(), RendererError> {
+        self.color_meshes
+            .get_mut(mesh.key)
+            .map(|mesh| mesh.render_state = render_state)
+            .ok_or(RendererError::InvalidHandle)?;
+        self.prepare_color_mesh_pipeline(mesh.key);
+        Ok(())
+    }
+    /// set how the triangles of a UvMesh are drawn
+    fn set_uv_mesh_render_state(
+        &mut self,
+        mesh: &UvMeshHandle,
+        render_state: RenderState,
+    ) -> Result<(), RendererError> {
+        self.uv_meshes
+            .get_mut(mesh.key)
+            .map(|mesh| mesh.render_state = render_state)
+            .ok_or(RendererError::InvalidHandle)?;
+        self.prepare_uv_mesh_pipeline(mesh.key);
+        Ok(())
+    }
+    /// set the viewport layers a Model is drawn in
+    fn set_model_layers(&mut self, model: &ModelHandle, layers: u32) -> Result<(), RendererError> {
+        self.models
+            .get_mut(model.key)
+            .map(|model| model.layers = layers)
+            .ok_or(RendererError::InvalidHandle)
+    }
+    /// set how the triangles of all meshes of a Model are drawn
+    fn set_model_render_state(
+        &mut self,
+        model: &ModelHandle,
+        render_state: RenderState,
+    ) -> Result<(), RendererError> {
+        self.models
+            .get_mut(model.key)
+            .map(|model| model.render_state = render_state)
+            .ok_or(RendererError::InvalidHandle)?;
+        self.prepare_model_pipeline(model.key);
+        Ok(())
     }
     /// get access to all lines
     fn lines(&mut self) -> &mut Vec<Line> {
         self.lines.lines()
     }
 }
-
-fn put_in_first_slot<T>(vec: &mut Vec<Option<T>>, object: T) -> usize {
-    for (i, o) in vec.iter_mut().enumerate() {
-        if o.is_none() {
-            *o = Some(object);
-            return i;
-        }
-    }
-    vec.push(Some(object));
-    vec.len() - 1
-}
@@ -1,2 +1,8 @@
 pub mod lights;
-pub mod meshes;
\ No newline at end of file
+pub mod meshes;
+pub mod picking;
+pub mod point_clouds;
+pub mod render_textures;
+pub mod sprites;
+pub mod viewports;
+pub mod windows;
@@ -0,0 +1,159 @@
+use crate::api::meshes::{ColorMeshHandle, ModelHandle, UvMeshHandle};
+use crate::mesh_utils::triangles;
+use crate::picking::{hit_mesh, MeshHit, Ray};
+use crate::viewport::PixelRect;
+use crate::{InstanceId, RenderState, WgpuRenderer};
+use cgmath::{Vector2, Vector3};
+
+/// the mesh a ray hit
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PickTarget {
+    ColorMesh(ColorMeshHandle),
+    UvMesh(UvMeshHandle),
+    /// the model and the index of the mesh in it that was hit
+    Model(ModelHandle, usize),
+}
+
+/// the closest point a ray hit
+#[derive(Copy, Clone, Debug)]
+pub struct PickHit {
+    pub target: PickTarget,
+    pub instance: InstanceId,
+    /// how far along the ray the hit is
+    pub distance: f32,
+    /// the hit point in world space
+    pub position: Vector3<f32>,
+    /// the normal of the triangle in world space
+    pub normal: Vector3<f32>,
+    /// the vertices of the triangle that was hit
+    pub triangle: [usize; 3],
+    /// how much every vertex of the triangle weighs at the hit point
+    pub barycentrics: Vector3<f32>,
+    /// the texture coordinates at the hit point, None for color meshes
+    pub uv: Option<Vector2<f32>>,
+}
+
+#[rustfmt::skip]
+pub trait PickApi {
+    fn pick(&self, x: f32, y: f32) -> Option<PickHit>;
+    fn pick_ray(&self, ray: Ray) -> Option<PickHit>;
+}
+
+impl PickApi for WgpuRenderer {
+    /// find what is under a point of the window, x and y are in pixels from the top left corner
+    /// the ray goes through the main camera, points outside of the main view hit nothing
+    fn pick(&self, x: f32, y: f32) -> Option<PickHit> {
+        let size = (self.sc_desc.width, self.sc_desc.height);
+        let rect = PixelRect::from_normalized(self.main_viewport_rect, size)?;
+        let ray = Ray::from_screen(self.camera.build_view_projection_matrix(), rect, x, y)?;
+        self.pick_ray(ray)
+    }
+    /// find the closest triangle the ray hits
+    /// only meshes and models in the layers of the main view can be hit
+    /// meshes drawn as lines, points or strips are skipped, their indices are not read as triangles
+    fn pick_ray(&self, ray: Ray) -> Option<PickHit> {
+        let layer_mask = self.main_layer_mask;
+        let mut closest: Option<PickHit> = None;
+        let max_distance =
+            |closest: &Option<PickHit>| closest.map_or(f32::INFINITY, |hit| hit.distance);
+
+        for (key, mesh) in self.color_meshes.iter_with_keys() {
+            if mesh.layers & layer_mask == 0 || !is_triangle_list(mesh.render_state) {
+                continue;
+            }
+            let hit = hit_mesh(
+                &ray,
+                &mesh.vertices,
+                triangles(mesh.vertices.len(), mesh.indices.as_ref()),
+                mesh.bounds.as_ref(),
+                mesh.instances.iter().map(|instance| &instance.transform),
+                max_distance(&closest),
+            );
+            if let Some(hit) = hit {
+                if let Some(instance) = mesh.instances.id_at(hit.instance) {
+                    let target = PickTarget::ColorMesh(ColorMeshHandle::new(key));
+                    closest = Some(pick_hit(target, instance, hit, None));
+                }
+            }
+        }
+        for (key, mesh) in self.uv_meshes.iter_with_keys() {
+            if mesh.layers & layer_mask == 0 || !is_triangle_list(mesh.render_state) {
+                continue;
+            }
+            let hit = hit_mesh(
+                &ray,
+                &mesh.vertices,
+                triangles(mesh.vertices.len(), mesh.indices.as_ref()),
+                mesh.bounds.as_ref(),
+                mesh.instances.iter(),
+                max_distance(&closest),
+            );
+            if let Some(hit) = hit {
+                if let Some(instance) = mesh.instances.id_at(hit.instance) {
+                    let uv = interpolate(&hit, |vertex| mesh.vertices[vertex].get_uv());
+                    let target = PickTarget::UvMesh(UvMeshHandle::new(key));
+                    closest = Some(pick_hit(target, instance, hit, Some(uv)));
+                }
+            }
+        }
+        for (key, model) in self.models.iter_with_keys() {
+            if model.layers & layer_mask == 0 || !is_triangle_list(model.render_state) {
+                continue;
+            }
+            for (index, mesh) in model.meshes.iter().enumerate() {
+                let hit = hit_mesh(
+                    &ray,
+                    &mesh.vertices,
+                    mesh.indices.chunks_exact(3).map(|triangle| {
+                        [
+                            triangle[0] as usize,
+                            triangle[1] as usize,
+                            triangle[2] as usize,
+                        ]
+                    }),
+                    mesh.bounds.as_ref(),
+                    model.instances.iter(),
+                    max_distance(&closest),
+                );
+                if let Some(hit) = hit {
+                    if let Some(instance) = model.instances.id_at(hit.instance) {
+                        let uv = interpolate(&hit, |vertex| mesh.vertices[vertex].uv.into());
+                        let target = PickTarget::Model(ModelHandle::new(key), index);
+                        closest = Some(pick_hit(target, instance, hit, Some(uv)));
+                    }
+                }
+            }
+        }
+        closest
+    }
+}
+
+/// the indices of a mesh are read three at a time, this only matches what is drawn for triangle lists
+fn is_triangle_list(state: RenderState) -> bool {
+    state.topology == wgpu::PrimitiveTopology::TriangleList
+}
+
+fn pick_hit(
+    target: PickTarget,
+    instance: InstanceId,
+    hit: MeshHit,
+    uv: Option<Vector2<f32>>,
+) -> PickHit {
+    PickHit {
+        target,
+        instance,
+        distance: hit.distance,
+        position: hit.position,
+        normal: hit.normal,
+        triangle: hit.triangle,
+        barycentrics: hit.barycentrics,
+        uv,
+    }
+}
+
+/// blend a value of the vertices of the triangle that was hit with the barycentrics of the hit
+fn interpolate(hit: &MeshHit, value: impl Fn(usize) -> Vector2<f32>) -> Vector2<f32> {
+    value(hit.triangle[0]) * hit.barycentrics.x
+        + value(hit.triangle[1]) * hit.barycentrics.y
+        + value(hit.triangle[2]) * hit.barycentrics.z
+}
@@ -0,0 +1,89 @@
+use crate::point_cloud::PointCloud;
+use crate::slots::SlotKey;
+use crate::{Point, PointSize, RendererError, WgpuRenderer};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PointCloudHandle {
+    key: SlotKey,
+}
+impl PointCloudHandle {
+    pub(crate) fn new(key: SlotKey) -> Self {
+        Self { key }
+    }
+}
+
+#[rustfmt::skip]
+pub trait PointCloudApi {
+    fn load_point_cloud(&mut self, points: &[Point]) -> PointCloudHandle;
+    fn remove_point_cloud(&mut self, cloud: &PointCloudHandle);
+    fn update_point_cloud(&mut self, cloud: &PointCloudHandle, points: &[Point]) -> Result<(), RendererError>;
+    fn set_point_cloud_size(&mut self, cloud: &PointCloudHandle, size: PointSize) -> Result<(), RendererError>;
+    fn set_point_cloud_lit(&mut self, cloud: &PointCloudHandle, lit: bool) -> Result<(), RendererError>;
+    fn set_point_cloud_layers(&mut self, cloud: &PointCloudHandle, layers: u32) -> Result<(), RendererError>;
+}
+
+impl PointCloudApi for WgpuRenderer {
+    /// load points that are drawn as squares facing the camera
+    /// their sizes are in pixels until set_point_cloud_size is called
+    fn load_point_cloud(&mut self, points: &[Point]) -> PointCloudHandle {
+        let cloud = PointCloud::new(&self.device, points);
+        PointCloudHandle::new(self.point_clouds.insert(cloud))
+    }
+    /// remove a PointCloud, a handle to a cloud that was already removed is ignored
+    fn remove_point_cloud(&mut self, cloud: &PointCloudHandle) {
+        self.point_clouds.remove(cloud.key);
+    }
+    /// replace all points of a PointCloud, the buffer is reused while the new points fit into it
+    /// fails if the cloud was removed
+    fn update_point_cloud(
+        &mut self,
+        cloud: &PointCloudHandle,
+        points: &[Point],
+    ) -> Result<(), RendererError> {
+        let cloud = self
+            .point_clouds
+            .get_mut(cloud.key)
+            .ok_or(RendererError::InvalidHandle)?;
+        cloud.update(&self.device, &self.queue, points);
+        Ok(())
+    }
+    /// set whether the sizes of the points are in pixels or in world units
+    fn set_point_cloud_size(
+        &mut self,
+        cloud: &PointCloudHandle,
+        size: PointSize,
+    ) -> Result<(), RendererError> {
+        let cloud = self
+            .point_clouds
+            .get_mut(cloud.key)
+            .ok_or(RendererError::InvalidHandle)?;
+        cloud.size = size;
+        cloud.write_uniforms(&self.queue);
+        Ok(())
+    }
+    /// light the points with the ambient light and the simple lights, the default is unlit
+    fn set_point_cloud_lit(
+        &mut self,
+        cloud: &PointCloudHandle,
+        lit: bool,
+    ) -> Result<(), RendererError> {
+        let cloud = self
+            .point_clouds
+            .get_mut(cloud.key)
+            .ok_or(RendererError::InvalidHandle)?;
+        cloud.lit = lit;
+        cloud.write_uniforms(&self.queue);
+        Ok(())
+    }
+    /// set the viewport layers a PointCloud is drawn in, the default is DEFAULT_LAYER
+    fn set_point_cloud_layers(
+        &mut self,
+        cloud: &PointCloudHandle,
+        layers: u32,
+    ) -> Result<(), RendererError> {
+        self.point_clouds
+            .get_mut(cloud.key)
+            .map(|cloud| cloud.layers = layers)
+            .ok_or(RendererError::InvalidHandle)
+    }
+}
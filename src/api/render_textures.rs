@@ -0,0 +1,95 @@
+use crate::render_texture::RenderTexture;
+use crate::{Camera, RenderTextureHandle, RendererError, UvMeshHandle, WgpuRenderer};
+
+#[rustfmt::skip]
+pub trait RenderTextureApi {
+    fn add_render_texture(&mut self, camera: Camera, size: (u32, u32)) -> RenderTextureHandle;
+    fn remove_render_texture(&mut self, render_texture: &RenderTextureHandle);
+    fn render_texture(&mut self, render_texture: &RenderTextureHandle) -> Result<&mut RenderTexture, RendererError>;
+    fn resize_render_texture(&mut self, render_texture: &RenderTextureHandle, size: (u32, u32)) -> Result<(), RendererError>;
+
+    fn set_uv_mesh_render_texture(&mut self, mesh: &UvMeshHandle, render_texture: Option<&RenderTextureHandle>) -> Result<(), RendererError>;
+}
+
+impl RenderTextureApi for WgpuRenderer {
+    /// add a camera that renders the scene into a texture of the given size in pixels
+    /// render textures are drawn every frame before the screen, in the order they were added
+    /// a mesh in it that shows a render texture added later shows what that one saw in the previous frame
+    fn add_render_texture(&mut self, camera: Camera, size: (u32, u32)) -> RenderTextureHandle {
+        let render_texture =
+            RenderTexture::new(&self.device, camera, size, &self.sc_desc, self.sample_count);
+        let key = self.render_textures.insert(render_texture);
+        self.render_texture_order.push(key);
+        RenderTextureHandle::new(key)
+    }
+    /// remove a render texture, meshes that showed it go back to their own texture
+    fn remove_render_texture(&mut self, render_texture: &RenderTextureHandle) {
+        self.render_textures.remove(render_texture.key());
+        self.render_texture_order
+            .retain(|&key| key != render_texture.key());
+        for mesh in self.uv_meshes.iter_mut() {
+            if mesh.render_texture == Some(render_texture.key()) {
+                mesh.render_texture = None;
+                mesh.update_texture(&self.device);
+            }
+        }
+    }
+    /// get access to a render texture to change its camera or layer mask
+    /// fails if the render texture was removed
+    fn render_texture(
+        &mut self,
+        render_texture: &RenderTextureHandle,
+    ) -> Result<&mut RenderTexture, RendererError> {
+        self.render_textures
+            .get_mut(render_texture.key())
+            .ok_or(RendererError::InvalidHandle)
+    }
+    /// change the size of a render texture in pixels
+    fn resize_render_texture(
+        &mut self,
+        render_texture: &RenderTextureHandle,
+        size: (u32, u32),
+    ) -> Result<(), RendererError> {
+        let key = render_texture.key();
+        let target = self
+            .render_textures
+            .get_mut(key)
+            .ok_or(RendererError::InvalidHandle)?;
+        target.resize(&self.device, size, &self.sc_desc, self.sample_count);
+
+        for mesh in self.uv_meshes.iter_mut() {
+            if mesh.render_texture == Some(key) {
+                mesh.bind_texture(&self.device, &target.color);
+            }
+        }
+        Ok(())
+    }
+    /// show a render texture on a UvMesh instead of its own texture
+    /// None switches back to the texture of the mesh
+    /// a mesh is never drawn into the render texture it shows, that would read and write the same texture
+    fn set_uv_mesh_render_texture(
+        &mut self,
+        mesh: &UvMeshHandle,
+        render_texture: Option<&RenderTextureHandle>,
+    ) -> Result<(), RendererError> {
+        let mesh = self
+            .uv_meshes
+            .get_mut(mesh.key())
+            .ok_or(RendererError::InvalidHandle)?;
+        match render_texture {
+            Some(handle) => {
+                let target = self
+                    .render_textures
+                    .get(handle.key())
+                    .ok_or(RendererError::InvalidHandle)?;
+                mesh.bind_texture(&self.device, &target.color);
+                mesh.render_texture = Some(handle.key());
+            }
+            None => {
+                mesh.update_texture(&self.device);
+                mesh.render_texture = None;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use crate::slots::SlotKey;
+use crate::sprites::Sprite;
+use crate::{BlendMode, Instances, RendererError, SpriteInstance, WgpuRenderer};
+use std::path::Path;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SpriteHandle {
+    key: SlotKey,
+}
+impl SpriteHandle {
+    pub(crate) fn new(key: SlotKey) -> Self {
+        Self { key }
+    }
+}
+
+#[rustfmt::skip]
+pub trait SpriteApi {
+    fn load_sprite<P: AsRef<Path>>(&mut self, texture: P) -> Result<SpriteHandle, RendererError>;
+    fn remove_sprite(&mut self, sprite: &SpriteHandle);
+    fn sprite_instances(&mut self, sprite: &SpriteHandle) -> Result<&mut Instances<SpriteInstance>, RendererError>;
+    fn update_sprite(&mut self, sprite: &SpriteHandle);
+    fn write_raw_texture_to_sprite(&mut self, sprite: &SpriteHandle, size: (u32, u32), data: &[u8]);
+    fn set_sprite_layers(&mut self, sprite: &SpriteHandle, layers: u32) -> Result<(), RendererError>;
+    fn set_sprite_blend_mode(&mut self, sprite: &SpriteHandle, blend_mode: BlendMode) -> Result<(), RendererError>;
+}
+
+impl SpriteApi for WgpuRenderer {
+    /// load a texture that is drawn on quads facing the camera, one for every instance
+    /// the instances can show different parts of the texture, so one texture atlas can hold many sprites
+    fn load_sprite<P: AsRef<Path>>(&mut self, texture: P) -> Result<SpriteHandle, RendererError> {
+        let sprite = Sprite::new(&self.device, &self.queue, texture)?;
+        let key = self.sprites.insert(sprite);
+        self.prepare_sprite_pipeline(key);
+        Ok(SpriteHandle::new(key))
+    }
+    /// remove a sprite, a handle to a sprite that was already removed is ignored
+    fn remove_sprite(&mut self, sprite: &SpriteHandle) {
+        self.sprites.remove(sprite.key);
+    }
+    /// get all the instances of a sprite
+    /// fails if the sprite was removed
+    fn sprite_instances(
+        &mut self,
+        sprite: &SpriteHandle,
+    ) -> Result<&mut Instances<SpriteInstance>, RendererError> {
+        self.sprites
+            .get_mut(sprite.key)
+            .map(|sprite| &mut sprite.instances)
+            .ok_or(RendererError::InvalidHandle)
+    }
+    /// update the instances of a sprite
+    /// this has to be called in order for any changes to take effect
+    /// only the instances that changed since the last update are written, so it is cheap to call every frame
+    fn update_sprite(&mut self, sprite: &SpriteHandle) {
+        if let Some(sprite) = self.sprites.get_mut(sprite.key) {
+            sprite.update(&self.device, &self.queue);
+        }
+    }
+    /// write a slice of bytes to the texture of a sprite
+    /// when the new texture is greater than the old one a new texture is created, this is a bit slower
+    fn write_raw_texture_to_sprite(
+        &mut self,
+        sprite: &SpriteHandle,
+        size: (u32, u32),
+        data: &[u8],
+    ) {
+        if let Some(sprite) = self.sprites.get_mut(sprite.key) {
+            if sprite
+                .texture
+                .write_raw(&self.device, &self.queue, size, data)
+            {
+                sprite.update_texture(&self.device);
+            }
+        }
+    }
+    /// set the viewport layers a sprite is drawn in, the default is DEFAULT_LAYER
+    fn set_sprite_layers(
+        &mut self,
+        sprite: &SpriteHandle,
+        layers: u32,
+    ) -> Result<(), RendererError> {
+        self.sprites
+            .get_mut(sprite.key)
+            .map(|sprite| sprite.layers = layers)
+            .ok_or(RendererError::InvalidHandle)
+    }
+    /// set how a sprite is blended with what is behind it, transparent sprites are sorted by their distance to the camera
+    /// the default is BlendMode::Opaque, which also draws the transparent parts of the texture
+    fn set_sprite_blend_mode(
+        &mut self,
+        sprite: &SpriteHandle,
+        blend_mode: BlendMode,
+    ) -> Result<(), RendererError> {
+        self.sprites
+            .get_mut(sprite.key)
+            .map(|sprite| sprite.blend_mode = blend_mode)
+            .ok_or(RendererError::InvalidHandle)?;
+        self.prepare_sprite_pipeline(sprite.key);
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use crate::{RendererError, Viewport, ViewportHandle, WgpuRenderer};
+
+#[rustfmt::skip]
+pub trait ViewportApi {
+    fn add_viewport(&mut self, viewport: Viewport) -> ViewportHandle;
+    fn remove_viewport(&mut self, viewport: &ViewportHandle);
+    fn viewport(&mut self, viewport: &ViewportHandle) -> Result<&mut Viewport, RendererError>;
+
+    fn set_main_viewport_rect(&mut self, rect: [f32; 4]);
+    fn set_main_layer_mask(&mut self, layer_mask: u32);
+}
+
+impl ViewportApi for WgpuRenderer {
+    /// add a viewport that is drawn on top of the main view and all viewports added before it
+    fn add_viewport(&mut self, viewport: Viewport) -> ViewportHandle {
+        let key = self.viewports.insert(viewport);
+        self.viewport_order.push(key);
+        ViewportHandle::new(key)
+    }
+    fn remove_viewport(&mut self, viewport: &ViewportHandle) {
+        self.viewports.remove(viewport.key());
+        self.viewport_order.retain(|&key| key != viewport.key());
+    }
+    /// get access to a viewport to move it or change its camera
+    /// fails if the viewport was removed
+    fn viewport(&mut self, viewport: &ViewportHandle) -> Result<&mut Viewport, RendererError> {
+        self.viewports
+            .get_mut(viewport.key())
+            .ok_or(RendererError::InvalidHandle)
+    }
+    /// the part of the screen the main camera draws into
+    /// x, y, width and height as fractions of the screen, the default is the whole screen
+    fn set_main_viewport_rect(&mut self, rect: [f32; 4]) {
+        self.main_viewport_rect = rect;
+    }
+    /// the layers the main camera draws, the default is all layers
+    fn set_main_layer_mask(&mut self, layer_mask: u32) {
+        self.main_layer_mask = layer_mask;
+    }
+}
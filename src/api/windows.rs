@@ -0,0 +1,71 @@
+use crate::window::ExtraWindow;
+use crate::{Camera, RendererError, WgpuRenderer, WindowHandle};
+
+#[rustfmt::skip]
+pub trait WindowApi {
+    fn add_window(&mut self, window: &winit::window::Window, camera: Camera) -> Result<WindowHandle, RendererError>;
+    fn remove_window(&mut self, window: &WindowHandle);
+    fn window(&mut self, window: &WindowHandle) -> Result<&mut ExtraWindow, RendererError>;
+    fn resize_window(&mut self, window: &WindowHandle, size: (i32, i32)) -> Result<(), RendererError>;
+    fn render_window(&mut self, window: &WindowHandle) -> Result<(), RendererError>;
+}
+
+impl WindowApi for WgpuRenderer {
+    /// show the scene in another window, seen through the given camera
+    /// fails if the window needs a different texture format than the main window
+    /// a window without an area, like a minimized one, is drawn once resize_window gives it a size
+    fn add_window(
+        &mut self,
+        window: &winit::window::Window,
+        camera: Camera,
+    ) -> Result<WindowHandle, RendererError> {
+        let surface = unsafe { self.instance.create_surface(window) };
+        // the pipelines are shared, so every window has to use the format they were created with
+        let format = self.adapter.get_swap_chain_preferred_format(&surface);
+        if format != self.sc_desc.format {
+            return Err(RendererError::IncompatibleSurface(format));
+        }
+        let size = window.inner_size();
+        let sc_desc = wgpu::SwapChainDescriptor {
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
+            format,
+            width: size.width,
+            height: size.height,
+            present_mode: self.sc_desc.present_mode,
+        };
+        let window = ExtraWindow::new(&self.device, camera, surface, sc_desc, self.sample_count);
+        Ok(WindowHandle::new(self.windows.insert(window)))
+    }
+    fn remove_window(&mut self, window: &WindowHandle) {
+        self.windows.remove(window.key());
+    }
+    /// get access to a window to change its camera, layer mask or paragraphs
+    /// fails if the window was removed
+    fn window(&mut self, window: &WindowHandle) -> Result<&mut ExtraWindow, RendererError> {
+        self.windows
+            .get_mut(window.key())
+            .ok_or(RendererError::InvalidHandle)
+    }
+    /// this has to be called when the window was resized, like resize for the main window
+    fn resize_window(
+        &mut self,
+        window: &WindowHandle,
+        size: (i32, i32),
+    ) -> Result<(), RendererError> {
+        let window = self
+            .windows
+            .get_mut(window.key())
+            .ok_or(RendererError::InvalidHandle)?;
+        window.resize(
+            &self.device,
+            (size.0.max(0) as u32, size.1.max(0) as u32),
+            self.sample_count,
+        );
+        Ok(())
+    }
+    /// draw a frame into the window and present it
+    /// update has to be called before, just like for render
+    fn render_window(&mut self, window: &WindowHandle) -> Result<(), RendererError> {
+        self.render_extra_window(window.key())
+    }
+}
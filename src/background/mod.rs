@@ -0,0 +1,69 @@
+use crate::lines::LineVertex;
+use cgmath::{Vector3, Vector4};
+
+/// what the screen is filled with before anything else is drawn
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Background {
+    /// clear the screen to a single color
+    Color([f64; 4]),
+    /// blend from one color at the top of the screen to another at the bottom
+    VerticalGradient { top: [f64; 4], bottom: [f64; 4] },
+    /// don't clear the screen and draw on top of what is already there
+    /// use this to composite the renderer over something else
+    /// only a headless renderer with one sample per pixel can use it, set_background fails otherwise
+    /// with msaa the old contents of the multisampled texture would be loaded instead of the target,
+    /// and the images of a swap chain have undefined contents after they were presented
+    None,
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Background::Color([0.0, 0.0, 0.0, 1.0])
+    }
+}
+
+impl Background {
+    /// what the forward pass does with the color target when it starts
+    pub(crate) fn load_op(&self) -> wgpu::LoadOp<wgpu::Color> {
+        match self {
+            Background::Color(color) => wgpu::LoadOp::Clear(to_wgpu_color(*color)),
+            // everything is drawn over by the gradient
+            Background::VerticalGradient { .. } => wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+            // set_background only allows it without msaa, so the color attachment is the target itself
+            Background::None => wgpu::LoadOp::Load,
+        }
+    }
+    /// the vertices of a full screen triangle strip, only a gradient has to be drawn
+    pub(crate) fn vertices(&self) -> Option<[LineVertex; 4]> {
+        if let Background::VerticalGradient { top, bottom } = self {
+            let top = to_vector(*top);
+            let bottom = to_vector(*bottom);
+            Some([
+                LineVertex::new(Vector3::new(-1.0, 1.0, 0.0), top),
+                LineVertex::new(Vector3::new(-1.0, -1.0, 0.0), bottom),
+                LineVertex::new(Vector3::new(1.0, 1.0, 0.0), top),
+                LineVertex::new(Vector3::new(1.0, -1.0, 0.0), bottom),
+            ])
+        } else {
+            None
+        }
+    }
+}
+
+pub(crate) fn to_wgpu_color(color: [f64; 4]) -> wgpu::Color {
+    wgpu::Color {
+        r: color[0],
+        g: color[1],
+        b: color[2],
+        a: color[3],
+    }
+}
+
+fn to_vector(color: [f64; 4]) -> Vector4<f32> {
+    Vector4::new(
+        color[0] as f32,
+        color[1] as f32,
+        color[2] as f32,
+        color[3] as f32,
+    )
+}
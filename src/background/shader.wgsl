@@ -0,0 +1,26 @@
+[[location(0)]] var<in> in_position: vec4<f32>;
+[[location(1)]] var<in> in_color: vec4<f32>;
+
+[[builtin(position)]]
+var<out> out_position: vec4<f32>;
+
+[[location(0)]]
+var<out> out_color: vec4<f32>;
+
+// the vertices are already in clip space, they cover the whole screen
+[[stage(vertex)]]
+fn vs_main() {
+    out_position = in_position;
+    out_color = in_color;
+}
+
+[[location(0)]]
+var<in> v_color: vec4<f32>;
+
+[[location(0)]]
+var<out> out_color_fs: vec4<f32>;
+
+[[stage(fragment)]]
+fn fs_main() {
+    out_color_fs = v_color;
+}
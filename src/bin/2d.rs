@@ -1,6 +1,6 @@
 use cgmath::{Matrix3, Point3, Vector3};
 use finger_paint_wgpu::cgmath::{SquareMatrix, Vector2};
-use finger_paint_wgpu::{Camera, HorizontalAlign, Paragraph, Resize, TextSection, Transform, UvVertex, VerticalAlign, ViewMatrixMode, WgpuRenderer, MeshApi, UvMeshHandle};
+use finger_paint_wgpu::{Background, Camera, HorizontalAlign, Indices, Paragraph, Resize, Shape, ShapeKind, TextSection, Transform, UvVertex, VerticalAlign, ViewMatrixMode, WgpuRenderer, MeshApi, UvMeshHandle, RendererConfig};
 use simple_winit::input::Input;
 use simple_winit::InputEvent;
 use std::time::Duration;
@@ -71,7 +71,8 @@ pub struct State {
 
 impl State {
     pub fn new(window: &simple_winit::winit::window::Window) -> Self {
-        let mut renderer = WgpuRenderer::new(window, Some(std::path::PathBuf::from("./")));
+        let mut renderer =
+            WgpuRenderer::new(window, RendererConfig::default().hot_reload_shader("./")).unwrap();
         let plane = renderer.load_uv_mesh(
             vec![
                 UvVertex::new(
@@ -95,9 +96,10 @@ impl State {
                     Vector2::new(1.0, 1.0),
                 ),
             ],
-            Some(vec![2, 1, 0, 1, 2, 3]),
+            Some(Indices::U16(vec![2, 1, 0, 1, 2, 3])),
             "",
-        );
+        )
+        .unwrap();
         Self {
             renderer,
             time: 0.0,
@@ -110,11 +112,14 @@ impl State {
 
 impl simple_winit::WindowLoop for State {
     fn init(&mut self) {
-        self.renderer.uv_mesh_instances(&self.plane).push(Transform {
-            position: Vector3::new(0.0, 1.0, 2.0),
-            rotation: Matrix3::identity(),
-            scale: Vector3::new(1.0, 1.0, 1.0),
-        });
+        self.renderer
+            .uv_mesh_instances(&self.plane)
+            .unwrap()
+            .push(Transform {
+                position: Vector3::new(0.0, 1.0, 2.0),
+                rotation: Matrix3::identity(),
+                scale: Vector3::new(1.0, 1.0, 1.0),
+            });
         self.renderer.update_uv_mesh(&self.plane);
         self.renderer.paragraphs().push(Paragraph {
             vertical_alignment: VerticalAlign::Top,
@@ -127,7 +132,25 @@ impl simple_winit::WindowLoop for State {
                 font: Default::default(),
             }],
         });
+        // a panel behind the frame time
+        let panel = ShapeKind::RoundedRect {
+            position: Vector2::new(4.0, 4.0),
+            size: Vector2::new(300.0, 60.0),
+            radius: 8.0,
+        };
+        self.renderer
+            .shapes()
+            .push(Shape::filled(panel.clone(), [0.0, 0.0, 0.0, 0.5]));
+        self.renderer
+            .shapes()
+            .push(Shape::stroked(panel, 2.0, [1.0, 1.0, 1.0, 0.8]).with_z(1));
         self.renderer.enable_lighting(false);
+        self.renderer
+            .set_background(Background::VerticalGradient {
+                top: [0.1, 0.1, 0.2, 1.0],
+                bottom: [0.02, 0.02, 0.05, 1.0],
+            })
+            .unwrap();
         *self.renderer.camera() = Camera::new(
             Point3::new(0.0, 5.0, 0.0),
             Point3::new(0.0, 0.0, 0.0),
@@ -176,7 +199,7 @@ impl simple_winit::WindowLoop for State {
     }
 
     fn render(&mut self) {
-        self.renderer.render();
+        self.renderer.render().unwrap();
     }
     fn on_close(&mut self) {}
 
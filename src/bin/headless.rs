@@ -0,0 +1,63 @@
+use cgmath::{Point3, Vector2, Vector3, Vector4};
+use finger_paint_wgpu::cgmath::SquareMatrix;
+use finger_paint_wgpu::{
+    Capture, ColorMeshInstance, ColorVertex, HorizontalAlign, Indices, Lighting, MeshApi,
+    Paragraph, RendererConfig, TextSection, Transform, VerticalAlign, WgpuRenderer,
+};
+
+fn main() {
+    let mut renderer = WgpuRenderer::new_headless(
+        (640, 480),
+        RendererConfig::default().hot_reload_shader("./"),
+    )
+    .unwrap();
+
+    let color = Vector4::new(1.0, 0.5, 0.0, 1.0);
+    let normal = Vector3::new(0.0, 0.0, 1.0);
+    let triangle = renderer.load_color_mesh(
+        vec![
+            ColorVertex::new(Vector3::new(-1.0, -1.0, 0.0), normal, color),
+            ColorVertex::new(Vector3::new(1.0, -1.0, 0.0), normal, color),
+            ColorVertex::new(Vector3::new(0.0, 1.0, 0.0), normal, color),
+        ],
+        Some(Indices::U16(vec![0, 1, 2])),
+    );
+    renderer
+        .color_mesh_instances(&triangle)
+        .unwrap()
+        .push(ColorMeshInstance {
+            transform: Transform {
+                position: Vector3::new(0.0, 0.0, 0.0),
+                rotation: cgmath::Matrix3::identity(),
+                scale: Vector3::new(1.0, 1.0, 1.0),
+            },
+            lighting: Lighting {
+                specular_strength: 0.0,
+                specular_spread: 1.0,
+                diffuse_strength: 1.0,
+            },
+        });
+    renderer.update_color_mesh(&triangle);
+
+    renderer.enable_lighting(false);
+    renderer.camera().set_position(Point3::new(0.0, 0.0, 3.0));
+    renderer
+        .camera()
+        .set_direction(Vector3::new(0.0, 0.0, -1.0));
+
+    renderer.paragraphs().push(Paragraph {
+        vertical_alignment: VerticalAlign::Top,
+        horizontal_alignment: HorizontalAlign::Left,
+        position: Vector2::new(0.0, 0.0),
+        sections: vec![TextSection {
+            text: "rendered without a window".into(),
+            color: [1.0, 1.0, 1.0, 1.0],
+            scale: 25.0,
+            font: Default::default(),
+        }],
+    });
+
+    renderer.update();
+    renderer.render().unwrap();
+    renderer.save_screenshot("headless.png").unwrap();
+}
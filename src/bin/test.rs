@@ -1,9 +1,10 @@
 use cgmath::{Matrix3, Point3, Vector3, Vector4};
 use finger_paint_wgpu::cgmath::{Deg, InnerSpace, Rad, SquareMatrix, Vector2};
 use finger_paint_wgpu::{
-    Camera, ColorMeshHandle, ColorMeshInstance, ColorVertex, HorizontalAlign, LightAttenuation,
-    Lighting, Line, LineVertex, MeshApi, Paragraph, RealLightApi, RealLightPublic, Resize,
-    TextSection, Transform, UvMeshHandle, UvVertex, VerticalAlign, ViewMatrixMode, WgpuRenderer,
+    Camera, ColorMeshHandle, ColorMeshInstance, ColorVertex, HorizontalAlign, Indices, InstanceId,
+    LightAttenuation, Lighting, Line, LineVertex, MeshApi, Paragraph, Primitive, RealLightApi,
+    RealLightHandle, RealLightPublic, RendererConfig, Resize, TextSection, Transform, UvMeshHandle,
+    UvVertex, VerticalAlign, ViewMatrixMode, Viewport, ViewportApi, WgpuRenderer,
 };
 use simple_winit::input::{Input, VirtualKeyCode};
 use simple_winit::InputEvent;
@@ -15,7 +16,8 @@ pub struct State {
     time: f32,
     cube_model: ColorMeshHandle,
     plane_model: UvMeshHandle,
-    cube_2: usize,
+    cube_2: Option<InstanceId>,
+    light: Option<RealLightHandle>,
     camera_controller: CameraController,
     average_frame_time: f32,
     lighting: bool,
@@ -23,43 +25,55 @@ pub struct State {
 
 impl State {
     pub fn new(window: &simple_winit::winit::window::Window) -> Self {
-        let mut renderer = WgpuRenderer::new(window, Some(std::path::PathBuf::from("./")));
+        let mut renderer = WgpuRenderer::new(
+            window,
+            RendererConfig::default()
+                .sample_count(4)
+                .hot_reload_shader("./"),
+        )
+        .unwrap();
 
-        let (cube_vertex_data, cube_index_data) = create_cube();
-        let cube_model = renderer.load_color_mesh(cube_vertex_data, Some(cube_index_data));
-        //let (plane_vertex_data, plane_index_data) = create_plane(20.0);
-        let plane_model = renderer.load_uv_mesh(
-            vec![
-                UvVertex::new(
-                    Vector3::new(0.0, 0.0, 0.0),
-                    Vector3::new(0.0, 1.0, 0.0),
-                    Vector2::new(0.0, 0.0),
-                ),
-                UvVertex::new(
-                    Vector3::new(1.0, 0.0, 0.0),
-                    Vector3::new(0.0, 1.0, 0.0),
-                    Vector2::new(0.0, 1.0),
-                ),
-                UvVertex::new(
-                    Vector3::new(0.0, 0.0, 1.0),
-                    Vector3::new(0.0, 1.0, 0.0),
-                    Vector2::new(1.0, 0.0),
-                ),
-                UvVertex::new(
-                    Vector3::new(1.0, 0.0, 1.0),
-                    Vector3::new(0.0, 1.0, 0.0),
-                    Vector2::new(1.0, 1.0),
-                ),
-            ],
-            Some(vec![2, 1, 0, 1, 2, 3]),
-            "grass_side.png",
+        let cube = Primitive::cube(2.0, 1);
+        let cube_model = renderer.load_color_mesh(
+            cube.color_vertices(Vector4::new(1.0, 1.0, 1.0, 1.0)),
+            Some(cube.indices()),
         );
+        //let (plane_vertex_data, plane_index_data) = create_plane(20.0);
+        let plane_model = renderer
+            .load_uv_mesh(
+                vec![
+                    UvVertex::new(
+                        Vector3::new(0.0, 0.0, 0.0),
+                        Vector3::new(0.0, 1.0, 0.0),
+                        Vector2::new(0.0, 0.0),
+                    ),
+                    UvVertex::new(
+                        Vector3::new(1.0, 0.0, 0.0),
+                        Vector3::new(0.0, 1.0, 0.0),
+                        Vector2::new(0.0, 1.0),
+                    ),
+                    UvVertex::new(
+                        Vector3::new(0.0, 0.0, 1.0),
+                        Vector3::new(0.0, 1.0, 0.0),
+                        Vector2::new(1.0, 0.0),
+                    ),
+                    UvVertex::new(
+                        Vector3::new(1.0, 0.0, 1.0),
+                        Vector3::new(0.0, 1.0, 0.0),
+                        Vector2::new(1.0, 1.0),
+                    ),
+                ],
+                Some(Indices::U16(vec![2, 1, 0, 1, 2, 3])),
+                "grass_side.png",
+            )
+            .unwrap();
         Self {
             renderer,
             time: 0.0,
             cube_model,
             plane_model,
-            cube_2: 0,
+            cube_2: None,
+            light: None,
             camera_controller: CameraController {
                 speed: 5.0,
                 mouse_sens: 0.005,
@@ -75,8 +89,10 @@ impl State {
 
 impl simple_winit::WindowLoop for State {
     fn init(&mut self) {
-        self.renderer
+        let cube_2 = self
+            .renderer
             .color_mesh_instances(&self.cube_model)
+            .unwrap()
             .push(ColorMeshInstance {
                 transform: Transform {
                     position: Vector3::new(0.9, 0.5, 2.0),
@@ -89,13 +105,14 @@ impl simple_winit::WindowLoop for State {
                     diffuse_strength: 1.0,
                 },
             });
-        self.cube_2 = self.renderer.color_mesh_instances(&self.cube_model).len() - 1;
+        self.cube_2 = Some(cube_2);
 
         self.renderer.update_uv_mesh(&self.plane_model);
         self.renderer.update_color_mesh(&self.cube_model);
 
         self.renderer
             .color_mesh_instances(&self.cube_model)
+            .unwrap()
             .push(ColorMeshInstance {
                 transform: Transform {
                     position: Vector3::new(0.0, 20.0, 0.0),
@@ -110,6 +127,7 @@ impl simple_winit::WindowLoop for State {
             });
         self.renderer
             .uv_mesh_instances(&self.plane_model)
+            .unwrap()
             .push(Transform {
                 position: Vector3::new(0.0, 1.0, 2.0),
                 rotation: Matrix3::identity(),
@@ -119,7 +137,7 @@ impl simple_winit::WindowLoop for State {
 
         //let sphere_model = self.renderer.load_model("res/grass.glb");
         let start = std::time::Instant::now();
-        let sphere_model = self.renderer.load_model("res/test/cottage.glb");
+        let sphere_model = self.renderer.load_model("res/test/cottage.glb").unwrap();
         dbg!(start.elapsed().as_secs_f64());
         let mut t = Transform::new();
 
@@ -132,6 +150,22 @@ impl simple_winit::WindowLoop for State {
 
         self.renderer.set_shadow_resolution([2048, 2048]);
 
+        // a top down minimap in the top right corner
+        self.renderer.add_viewport(Viewport::new(
+            Camera::new(
+                Point3::new(0.0, 40.0, 0.0),
+                Point3::new(0.0, 0.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0),
+                1.0,
+                ViewMatrixMode::Perspective {
+                    near: 0.1,
+                    far: 100.0,
+                    fov: PI / 3.0,
+                },
+            ),
+            [0.75, 0.0, 0.25, 0.25],
+        ));
+
         let pos: [f32; 3] = self.renderer.camera().get_position().into();
         self.renderer.paragraphs().push(Paragraph {
             vertical_alignment: VerticalAlign::Top,
@@ -181,25 +215,20 @@ impl simple_winit::WindowLoop for State {
         let dir: [f32; 3] = self.renderer.camera().get_direction().into();
         self.renderer.paragraphs()[0].sections[1].text =
             format!("pos: {:?} \ndirection: {:?}", pos, dir,);
-        self.renderer.color_mesh_instances(&self.cube_model)[self.cube_2]
-            .transform
-            .position[1] = self.time.cos() * 2.0;
-
-        let rotation: Matrix3<f32> = self.renderer.color_mesh_instances(&self.cube_model)
-            [self.cube_2]
-            .transform
-            .rotation;
-        self.renderer.color_mesh_instances(&self.cube_model)[self.cube_2]
-            .transform
-            .rotation = Matrix3::from_angle_x(Rad(dt)) * rotation;
-
-        self.renderer.color_mesh_instances(&self.cube_model)[self.cube_2]
-            .transform
-            .position = Vector3::new(
-            self.time.cos() * 5.0,
-            (self.time + self.time.sin()).cos() * 5.0 + 20.0,
-            self.time.sin() * 5.0,
-        );
+        if let Some(cube_2) = self.cube_2 {
+            let instances = self
+                .renderer
+                .color_mesh_instances(&self.cube_model)
+                .unwrap();
+            if let Some(cube) = instances.get_mut(cube_2) {
+                cube.transform.rotation = Matrix3::from_angle_x(Rad(dt)) * cube.transform.rotation;
+                cube.transform.position = Vector3::new(
+                    self.time.cos() * 5.0,
+                    (self.time + self.time.sin()).cos() * 5.0 + 20.0,
+                    self.time.sin() * 5.0,
+                );
+            }
+        }
 
         if input.key_pressed(VirtualKeyCode::L) {
             self.lighting = !self.lighting;
@@ -209,11 +238,14 @@ impl simple_winit::WindowLoop for State {
             self.renderer.set_shadow_resolution([100, 100]);
         }
 
-        if input.key_pressed(VirtualKeyCode::Y) && self.renderer.get_real_light(0).is_some() {
-            self.renderer.remove_real_light(0);
+        if input.key_pressed(VirtualKeyCode::Y) {
+            if let Some(light) = self.light.take() {
+                self.renderer.remove_real_light(&light);
+            }
         }
-        if input.key_pressed(VirtualKeyCode::G) && self.renderer.get_real_light(0).is_none() {
-            self.renderer
+        if input.key_pressed(VirtualKeyCode::G) && self.light.is_none() {
+            let light = self
+                .renderer
                 .add_real_light(RealLightPublic {
                     camera: Camera::new(
                         Point3::new(0.0, 35.0, 0.0),
@@ -235,12 +267,15 @@ impl simple_winit::WindowLoop for State {
                     },
                 })
                 .unwrap();
+            self.light = Some(light);
         }
         if input.key_held(VirtualKeyCode::LAlt) {
-            if let Some(mut l) = self.renderer.get_real_light(0) {
-                l.camera = *self.renderer.camera();
-                self.renderer.set_real_light(0, l);
-                self.renderer.update_real_lights();
+            if let Some(light) = &self.light {
+                if let Some(mut l) = self.renderer.get_real_light(light) {
+                    l.camera = *self.renderer.camera();
+                    self.renderer.set_real_light(light, l).unwrap();
+                    self.renderer.update_real_lights();
+                }
             }
         }
 
@@ -253,7 +288,7 @@ impl simple_winit::WindowLoop for State {
     }
 
     fn render(&mut self) {
-        self.renderer.render();
+        self.renderer.render().unwrap();
     }
     fn on_close(&mut self) {}
 
@@ -269,52 +304,6 @@ fn main() {
 }
 
 #[rustfmt::skip]
-pub fn create_cube() -> (Vec<ColorVertex>, Vec<u16>) {
-    let vertex_data = [
-        // top (0, 0, 1)
-        ColorVertex::new([-1.0, -1.0, 1.0].into(),[0.0, 0.0, 1.0].into(),[1.0, 1.0, 1.0, 1.0].into()),
-        ColorVertex::new([1.0, -1.0, 1.0].into(),[0.0, 0.0, 1.0].into(),[1.0, 1.0, 1.0, 1.0].into()),
-        ColorVertex::new([1.0, 1.0, 1.0].into(),[0.0, 0.0, 1.0].into(),[1.0, 1.0, 1.0, 1.0].into()),
-        ColorVertex::new([-1.0, 1.0, 1.0].into(),[0.0, 0.0, 1.0].into(),[1.0, 1.0, 1.0, 1.0].into()),
-        // bottom (0, 0, -1)
-        ColorVertex::new([-1.0, 1.0, -1.0].into(),[0.0, 0.0, -1.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        ColorVertex::new([1.0, 1.0, -1.0].into(), [0.0, 0.0, -1.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        ColorVertex::new([1.0, -1.0, -1.0].into(), [0.0, 0.0, -1.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        ColorVertex::new([-1.0, -1.0, -1.0].into(), [0.0, 0.0, -1.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        // right (1, 0, 0)
-        ColorVertex::new([1.0, -1.0, -1.0].into(), [1.0, 0.0, 0.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        ColorVertex::new([1.0, 1.0, -1.0].into(), [1.0, 0.0, 0.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        ColorVertex::new([1.0, 1.0, 1.0].into(), [1.0, 0.0, 0.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        ColorVertex::new([1.0, -1.0, 1.0].into(), [1.0, 0.0, 0.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        // left (-1, 0, 0)
-        ColorVertex::new([-1.0, -1.0, 1.0].into(), [-1.0, 0.0, 0.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        ColorVertex::new([-1.0, 1.0, 1.0].into(), [-1.0, 0.0, 0.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        ColorVertex::new([-1.0, 1.0, -1.0].into(), [-1.0, 0.0, 0.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        ColorVertex::new([-1.0, -1.0, -1.0].into(), [-1.0, 0.0, 0.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        // front (0, 1, 0)
-        ColorVertex::new([1.0, 1.0, -1.0].into(), [0.0, 1.0, 0.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        ColorVertex::new([-1.0, 1.0, -1.0].into(), [0.0, 1.0, 0.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        ColorVertex::new([-1.0, 1.0, 1.0].into(), [0.0, 1.0, 0.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        ColorVertex::new([1.0, 1.0, 1.0].into(), [0.0, 1.0, 0.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        // back (0, -1, 0)
-        ColorVertex::new([1.0, -1.0, 1.0].into(), [0.0, -1.0, 0.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        ColorVertex::new([-1.0, -1.0, 1.0].into(), [0.0, -1.0, 0.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        ColorVertex::new([-1.0, -1.0, -1.0].into(), [0.0, -1.0, 0.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-        ColorVertex::new([1.0, -1.0, -1.0].into(), [0.0, -1.0, 0.0].into(), [1.0, 1.0, 1.0, 1.0].into()),
-    ];
-
-    let index_data: &[u16] = &[
-        0, 1, 2, 2, 3, 0, // top
-        4, 5, 6, 6, 7, 4, // bottom
-        8, 9, 10, 10, 11, 8, // right
-        12, 13, 14, 14, 15, 12, // left
-        16, 17, 18, 18, 19, 16, // front
-        20, 21, 22, 22, 23, 20, // back
-    ];
-
-    (vertex_data.to_vec(), index_data.to_vec())
-}
-
 pub fn create_plane(size: f32) -> (Vec<ColorVertex>, Vec<u16>) {
     let vertex_data = [
         ColorVertex::new(
@@ -0,0 +1,234 @@
+use crate::{Camera, Transform};
+use cgmath::{ElementWise, InnerSpace, Matrix, Matrix4, Vector3, Vector4};
+use std::ops::Range;
+
+/// an axis aligned bounding box
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vector3<f32>,
+    pub max: Vector3<f32>,
+}
+
+impl Aabb {
+    /// the smallest box around the points, None if there are no points
+    pub fn from_points<I: IntoIterator<Item = Vector3<f32>>>(points: I) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(
+            Self {
+                min: first,
+                max: first,
+            },
+            |aabb, point| Self {
+                min: Vector3::new(
+                    aabb.min.x.min(point.x),
+                    aabb.min.y.min(point.y),
+                    aabb.min.z.min(point.z),
+                ),
+                max: Vector3::new(
+                    aabb.max.x.max(point.x),
+                    aabb.max.y.max(point.y),
+                    aabb.max.z.max(point.z),
+                ),
+            },
+        ))
+    }
+    pub fn center(&self) -> Vector3<f32> {
+        (self.min + self.max) / 2.0
+    }
+    /// half the size of the box along every axis
+    pub fn half_extents(&self) -> Vector3<f32> {
+        (self.max - self.min) / 2.0
+    }
+    /// the smallest axis aligned box around this box after it was moved by the transform
+    pub fn transformed(&self, transform: &Transform) -> Self {
+        let center = transform.transform_point(self.center());
+        let extents = self.half_extents().mul_element_wise(transform.scale);
+        let rotation = transform.rotation;
+        // every axis of the new box is as long as the rotated extents reach along it
+        let reach = |row: usize| {
+            let row = rotation.row(row);
+            row.x.abs() * extents.x.abs()
+                + row.y.abs() * extents.y.abs()
+                + row.z.abs() * extents.z.abs()
+        };
+        let extents = Vector3::new(reach(0), reach(1), reach(2));
+        Self {
+            min: center - extents,
+            max: center + extents,
+        }
+    }
+}
+
+/// a sphere that contains every point of a mesh
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vector3<f32>,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    /// the sphere after it was moved by the transform
+    /// the rotation of the transform is expected to not stretch anything, the scale may
+    pub fn transformed(&self, transform: &Transform) -> Self {
+        let scale = transform
+            .scale
+            .x
+            .abs()
+            .max(transform.scale.y.abs())
+            .max(transform.scale.z.abs());
+        Self {
+            center: transform.transform_point(self.center),
+            radius: self.radius * scale,
+        }
+    }
+}
+
+/// the box and the sphere around a mesh
+/// the sphere is centered on the box and only as large as the farthest point needs it to be
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bounds {
+    pub aabb: Aabb,
+    pub sphere: BoundingSphere,
+}
+
+impl Bounds {
+    /// None if there are no points
+    pub fn from_points<I: IntoIterator<Item = Vector3<f32>>>(points: I) -> Option<Self> {
+        let points = points.into_iter().collect::<Vec<_>>();
+        let aabb = Aabb::from_points(points.iter().copied())?;
+        let center = aabb.center();
+        let radius = points
+            .iter()
+            .map(|point| (point - center).magnitude())
+            .fold(0.0, f32::max);
+        Some(Self {
+            aabb,
+            sphere: BoundingSphere { center, radius },
+        })
+    }
+    pub fn transformed(&self, transform: &Transform) -> Self {
+        Self {
+            aabb: self.aabb.transformed(transform),
+            sphere: self.sphere.transformed(transform),
+        }
+    }
+}
+
+/// the space a camera can see, everything outside of it does not have to be drawn
+#[derive(Copy, Clone, Debug)]
+pub struct Frustum {
+    /// left, right, bottom, top, near and far, the normals point inwards
+    planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+    /// the frustum of a view projection matrix with a depth range from 0 to 1, like the ones of Camera
+    pub fn from_matrix(view_proj: Matrix4<f32>) -> Self {
+        let rows = [
+            view_proj.row(0),
+            view_proj.row(1),
+            view_proj.row(2),
+            view_proj.row(3),
+        ];
+        let mut planes = [
+            rows[3] + rows[0],
+            rows[3] - rows[0],
+            rows[3] + rows[1],
+            rows[3] - rows[1],
+            rows[2],
+            rows[3] - rows[2],
+        ];
+        for plane in planes.iter_mut() {
+            let length = plane.truncate().magnitude();
+            if length > 0.0 {
+                *plane /= length;
+            }
+        }
+        Self { planes }
+    }
+    pub fn from_camera(camera: &Camera) -> Self {
+        Self::from_matrix(camera.build_view_projection_matrix())
+    }
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(sphere.center) + plane.w >= -sphere.radius)
+    }
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            // the corner of the box that is farthest along the normal of the plane
+            let corner = Vector3::new(
+                if plane.x >= 0.0 {
+                    aabb.max.x
+                } else {
+                    aabb.min.x
+                },
+                if plane.y >= 0.0 {
+                    aabb.max.y
+                } else {
+                    aabb.min.y
+                },
+                if plane.z >= 0.0 {
+                    aabb.max.z
+                } else {
+                    aabb.min.z
+                },
+            );
+            plane.truncate().dot(corner) + plane.w >= 0.0
+        })
+    }
+    /// the cheaper sphere test is done first
+    pub fn intersects(&self, bounds: &Bounds) -> bool {
+        self.intersects_sphere(&bounds.sphere) && self.intersects_aabb(&bounds.aabb)
+    }
+}
+
+/// bring the bounds of the instances up to date after the instances in the changed ranges were written
+/// instances without bounds yet, because they are new or the bounds were cleared, are always computed
+pub(crate) fn update_instance_bounds<T>(
+    instance_bounds: &mut Vec<Bounds>,
+    instances: &[T],
+    changed: &[Range<usize>],
+    bounds_of: impl Fn(&T) -> Bounds,
+) {
+    instance_bounds.truncate(instances.len());
+    let known = instance_bounds.len();
+    for range in changed {
+        let end = range.end.min(known);
+        let start = range.start.min(end);
+        for (bounds, instance) in instance_bounds[start..end]
+            .iter_mut()
+            .zip(&instances[start..end])
+        {
+            *bounds = bounds_of(instance);
+        }
+    }
+    instance_bounds.extend(instances[known..].iter().map(bounds_of));
+}
+
+/// the ranges of instances that have to be drawn, neighbouring instances are merged into one range
+/// without a frustum, or without bounds for every instance in the buffer, all instances are drawn
+pub(crate) fn visible_instances(
+    instance_bounds: &[Bounds],
+    instances_in_buffer: usize,
+    frustum: Option<&Frustum>,
+) -> Vec<Range<u32>> {
+    let frustum = match frustum {
+        Some(frustum) if instance_bounds.len() == instances_in_buffer => frustum,
+        _ if instances_in_buffer == 0 => return vec![],
+        _ => return vec![0..instances_in_buffer as u32],
+    };
+    let mut ranges: Vec<Range<u32>> = vec![];
+    for (index, bounds) in instance_bounds.iter().enumerate() {
+        if !frustum.intersects(bounds) {
+            continue;
+        }
+        let index = index as u32;
+        match ranges.last_mut() {
+            Some(range) if range.end == index => range.end += 1,
+            _ => ranges.push(index..index + 1),
+        }
+    }
+    ranges
+}
@@ -0,0 +1,135 @@
+use crate::render_target::RenderTarget;
+use crate::{RendererError, WgpuRenderer};
+use futures::executor::block_on;
+use image::RgbaImage;
+use std::path::Path;
+
+pub trait Capture {
+    fn capture_frame(&mut self) -> Result<RgbaImage, RendererError>;
+    fn request_capture(&mut self);
+    fn save_screenshot<P: AsRef<Path>>(&mut self, path: P) -> Result<(), RendererError>;
+}
+
+impl Capture for WgpuRenderer {
+    /// read the last rendered frame back from the gpu
+    /// a headless renderer copies the texture it drew into, so every frame can be captured after render
+    /// the images of a swap chain can't be read once they are presented, so a window only has the frame rendered after request_capture
+    /// fails if there is no rendered frame to capture
+    /// it waits for the gpu to finish, so it is too slow to call every frame
+    fn capture_frame(&mut self) -> Result<RgbaImage, RendererError> {
+        match &self.target {
+            RenderTarget::Texture(texture) if self.frame_rendered => self.read_texture(texture),
+            RenderTarget::Texture(_) => Err(RendererError::Capture),
+            RenderTarget::Window { .. } => self.captured_frame.take().ok_or(RendererError::Capture),
+        }
+    }
+    /// keep a copy of the next frame that is rendered into the window for capture_frame
+    /// a headless renderer can always capture its last frame, so this does nothing for it
+    fn request_capture(&mut self) {
+        if let RenderTarget::Window { .. } = self.target {
+            self.capture_requested = true;
+        }
+    }
+    /// capture the last rendered frame and save it to a file
+    /// the image format is chosen by the extension of the path
+    fn save_screenshot<P: AsRef<Path>>(&mut self, path: P) -> Result<(), RendererError> {
+        self.capture_frame()?.save(path)?;
+        Ok(())
+    }
+}
+
+impl WgpuRenderer {
+    /// draw the frame into the frame of the window and into a texture that can be read, then keep the image of it
+    pub(crate) fn render_and_capture(
+        &mut self,
+        frame: &wgpu::TextureView,
+    ) -> Result<(), RendererError> {
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("capture target"),
+            size: wgpu::Extent3d {
+                width: self.sc_desc.width,
+                height: self.sc_desc.height,
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.sc_desc.format,
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.render_to_views(&[frame, &view]);
+        self.captured_frame = Some(self.read_texture(&texture)?);
+        self.capture_requested = false;
+        Ok(())
+    }
+    /// copy a texture with the size and format of the frames into an image
+    fn read_texture(&self, texture: &wgpu::Texture) -> Result<RgbaImage, RendererError> {
+        let width = self.sc_desc.width;
+        let height = self.sc_desc.height;
+        let extent = wgpu::Extent3d {
+            width,
+            height,
+            depth: 1,
+        };
+
+        // every row in the buffer has to start at a multiple of COPY_BYTES_PER_ROW_ALIGNMENT
+        let unpadded_bytes_per_row = width * 4;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = (unpadded_bytes_per_row + align - 1) / align * align;
+
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("capture buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsage::MAP_READ | wgpu::BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("capture"),
+            });
+        encoder.copy_texture_to_buffer(
+            wgpu::TextureCopyView {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            wgpu::BufferCopyView {
+                buffer: &buffer,
+                layout: wgpu::TextureDataLayout {
+                    offset: 0,
+                    bytes_per_row: padded_bytes_per_row,
+                    rows_per_image: height,
+                },
+            },
+            extent,
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let mapping = slice.map_async(wgpu::MapMode::Read);
+        self.device.poll(wgpu::Maintain::Wait);
+        block_on(mapping)?;
+
+        let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+        {
+            let data = slice.get_mapped_range();
+            for row in data.chunks(padded_bytes_per_row as usize) {
+                pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+            }
+        }
+        buffer.unmap();
+
+        if let wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb =
+            self.sc_desc.format
+        {
+            for pixel in pixels.chunks_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        RgbaImage::from_raw(width, height, pixels).ok_or(RendererError::Capture)
+    }
+}
@@ -1,71 +1,136 @@
+use crate::bounds::{update_instance_bounds, Bounds};
 use crate::color_mesh::instance::{ColorInstanceRaw, ColorMeshInstance};
 use crate::color_mesh::ColorVertex;
-use wgpu::util::{BufferInitDescriptor, DeviceExt};
-use wgpu::{Buffer, BufferDescriptor, BufferUsage, Device};
+use crate::growable_buffer::{write_indices, GrowableBuffer};
+use crate::indices::DrawRange;
+use crate::{BlendMode, Indices, Instances, RenderState, DEFAULT_LAYER};
+use wgpu::{BufferUsage, Device, Queue};
 
 use cgmath::Vector3;
 
 pub struct ColorMesh {
     pub color: Vector3<f32>,
-    pub instances: Vec<ColorMeshInstance>,
-    pub vertex_buf: wgpu::Buffer,
-    pub index_buf: Option<wgpu::Buffer>,
+    /// a copy of the geometry in the buffers, for picking
+    pub vertices: Vec<ColorVertex>,
+    pub indices: Option<Indices>,
+    pub instances: Instances<ColorMeshInstance>,
+    pub vertex_buf: GrowableBuffer,
+    pub index_buf: Option<GrowableBuffer>,
     pub vertex_count: usize,
     pub index_count: usize,
-    pub instance_buffer: Buffer,
+    /// the width of the indices in index_buf
+    pub index_format: wgpu::IndexFormat,
+    pub instance_buffer: GrowableBuffer,
     pub instances_in_buffer: usize,
+    /// the viewport layers this mesh is drawn in
+    pub layers: u32,
+    /// how the mesh is blended with what is behind it
+    pub blend_mode: BlendMode,
+    /// how the triangles of the mesh are drawn
+    pub render_state: RenderState,
+    /// the box and sphere around the vertices, None if there are no vertices
+    pub bounds: Option<Bounds>,
+    /// the bounds of every instance in the instance buffer, used to skip instances no camera sees
+    pub(crate) instance_bounds: Vec<Bounds>,
 }
 
 impl ColorMesh {
     pub fn from_vertices_and_indices(
         device: &Device,
         vertices: Vec<ColorVertex>,
-        indices: Option<Vec<u16>>,
+        indices: Option<Indices>,
     ) -> Self {
-        let instances = vec![];
-        let instance_buffer = device.create_buffer(&BufferDescriptor {
-            label: Some("model instance buffer"),
-            size: 0,
-            usage: BufferUsage::VERTEX,
-            mapped_at_creation: false,
-        });
+        let instances = Instances::new();
+        let instance_buffer =
+            GrowableBuffer::new(device, "instance vertex buffer", BufferUsage::VERTEX, &[]);
 
         Self {
             color: Vector3::new(1.0, 0.0, 1.0),
-            vertex_buf: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Cubes Vertex Buffer"),
-                contents: bytemuck::cast_slice(&vertices),
-                usage: BufferUsage::VERTEX,
+            vertex_buf: GrowableBuffer::new(
+                device,
+                "color mesh vertex buffer",
+                BufferUsage::VERTEX,
+                bytemuck::cast_slice(&vertices),
+            ),
+            index_buf: indices.as_ref().map(|indices| {
+                GrowableBuffer::new(
+                    device,
+                    "color mesh index buffer",
+                    BufferUsage::INDEX,
+                    indices.as_bytes(),
+                )
             }),
-            index_buf: indices.as_ref().map(|indices| device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Cubes Index Buffer"),
-                contents: bytemuck::cast_slice(&indices),
-                usage: BufferUsage::INDEX,
-            })),
+            bounds: Bounds::from_points(vertices.iter().map(ColorVertex::get_position)),
+            instance_bounds: vec![],
             vertex_count: vertices.len(),
-            index_count: if let Some(indices) = indices {
-                indices.len()
-            } else {
-                0
-            },
+            index_count: indices.as_ref().map_or(0, Indices::len),
+            index_format: indices
+                .as_ref()
+                .map_or(wgpu::IndexFormat::Uint16, Indices::format),
             instances_in_buffer: instances.len(),
             instances,
             instance_buffer,
+            layers: DEFAULT_LAYER,
+            blend_mode: BlendMode::Opaque,
+            render_state: RenderState::default(),
+            vertices,
+            indices,
         }
     }
-    pub fn update(&mut self, device: &Device) {
-        self.instance_buffer = device.create_buffer_init(&BufferInitDescriptor {
-            label: Some("instance vertex buffer"),
-            contents: bytemuck::cast_slice(
-                &self
-                    .instances
-                    .iter()
-                    .map(|instance: &ColorMeshInstance| instance.into())
-                    .collect::<Vec<ColorInstanceRaw>>(),
-            ),
-            usage: BufferUsage::VERTEX,
-        });
+    /// write the instances that changed since the last update to the GPU
+    pub fn update(&mut self, device: &Device, queue: &Queue) {
+        let changed = self.instances.upload(
+            device,
+            queue,
+            &mut self.instance_buffer,
+            ColorInstanceRaw::from,
+        );
         self.instances_in_buffer = self.instances.len();
+        match &self.bounds {
+            Some(bounds) => update_instance_bounds(
+                &mut self.instance_bounds,
+                &self.instances,
+                &changed,
+                |instance| bounds.transformed(&instance.transform),
+            ),
+            None => self.instance_bounds.clear(),
+        }
+    }
+    /// replace the vertices and indices, the buffers are reused if the new geometry fits into them
+    pub fn update_geometry(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        vertices: &[ColorVertex],
+        indices: Option<&Indices>,
+    ) {
+        self.vertex_buf
+            .write(device, queue, bytemuck::cast_slice(vertices));
+        self.vertex_count = vertices.len();
+        self.bounds = Bounds::from_points(vertices.iter().map(ColorVertex::get_position));
+        // the instances are not culled until the next update
+        self.instance_bounds.clear();
+        write_indices(
+            device,
+            queue,
+            &mut self.index_buf,
+            "color mesh index buffer",
+            indices,
+        );
+        self.index_count = indices.map_or(0, Indices::len);
+        self.index_format = indices.map_or(wgpu::IndexFormat::Uint16, Indices::format);
+        self.vertices = vertices.to_vec();
+        self.indices = indices.cloned();
+    }
+    /// the format of the indices, None if the mesh is drawn without indices
+    pub(crate) fn indices_format(&self) -> Option<wgpu::IndexFormat> {
+        self.index_buf.as_ref().map(|_| self.index_format)
+    }
+    pub(crate) fn draw_range(&self) -> DrawRange {
+        DrawRange::new(
+            self.vertex_count,
+            self.index_buf.as_ref().map(|_| self.index_count),
+        )
     }
     pub fn is_empty(&self) -> bool {
         self.instances_in_buffer == 0
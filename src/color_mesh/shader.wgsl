@@ -241,8 +241,8 @@ fn fs_main() {
             }
         }
 
-        // multiply the light by material color
-        out_color_fs = color * v_color;
+        // multiply the light by material color, the light does not change how transparent it is
+        out_color_fs = vec4<f32>(color.xyz * v_color.xyz, v_color.w);
     } else {
         out_color_fs = v_color;
     }
@@ -0,0 +1,118 @@
+use crate::Background;
+use std::path::PathBuf;
+
+/// Settings used when creating a WgpuRenderer.
+/// Start from the default and only change what you need:
+/// ```ignore
+/// let config = RendererConfig::default().vsync(true).max_real_lights(4);
+/// let renderer = WgpuRenderer::new(&window, config)?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct RendererConfig {
+    pub(crate) backends: wgpu::BackendBit,
+    pub(crate) power_preference: wgpu::PowerPreference,
+    pub(crate) present_mode: wgpu::PresentMode,
+    pub(crate) max_real_lights: u32,
+    pub(crate) font: &'static [u8],
+    pub(crate) background: Background,
+    pub(crate) sample_count: u32,
+    pub(crate) shadow_resolution: [u32; 2],
+    pub(crate) limits: wgpu::Limits,
+    pub(crate) hot_reload_shader: Option<PathBuf>,
+}
+
+impl Default for RendererConfig {
+    fn default() -> Self {
+        Self {
+            backends: wgpu::BackendBit::VULKAN,
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            present_mode: wgpu::PresentMode::Immediate,
+            max_real_lights: 10,
+            font: include_bytes!("../res/Inconsolata-Regular.ttf"),
+            background: Background::default(),
+            sample_count: 1,
+            shadow_resolution: [512, 512],
+            limits: wgpu::Limits::default(),
+            hot_reload_shader: None,
+        }
+    }
+}
+
+impl RendererConfig {
+    /// the graphics apis the renderer is allowed to use
+    /// the default is Vulkan
+    pub fn backends(mut self, backends: wgpu::BackendBit) -> Self {
+        self.backends = backends;
+        self
+    }
+    /// which GPU to prefer if there is more than one
+    /// the default is HighPerformance
+    pub fn power_preference(mut self, power_preference: wgpu::PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+    /// how frames are presented to the window
+    /// the default is Immediate
+    pub fn present_mode(mut self, present_mode: wgpu::PresentMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+    /// shorthand for present_mode
+    /// true -> Fifo, frames are synced to the refresh rate of the monitor
+    /// false -> Immediate
+    pub fn vsync(self, vsync: bool) -> Self {
+        self.present_mode(if vsync {
+            wgpu::PresentMode::Fifo
+        } else {
+            wgpu::PresentMode::Immediate
+        })
+    }
+    /// how many real lights (lights that cast shadows) can exist at the same time
+    /// every real light needs one layer of the shadow texture
+    /// the default is 10
+    pub fn max_real_lights(mut self, max_real_lights: u32) -> Self {
+        self.max_real_lights = max_real_lights;
+        self
+    }
+    /// the font used by TextSections with the default FontId
+    /// the default is Inconsolata
+    pub fn font(mut self, font: &'static [u8]) -> Self {
+        self.font = font;
+        self
+    }
+    /// the color the screen is cleared to before every frame
+    /// shorthand for background(Background::Color(clear_color))
+    pub fn clear_color(self, clear_color: [f64; 4]) -> Self {
+        self.background(Background::Color(clear_color))
+    }
+    /// what the screen is filled with before every frame
+    /// the default is black
+    pub fn background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+    /// the number of samples per pixel used for anti-aliasing (msaa)
+    /// has to be 1, 2, 4 or 8, creating the renderer fails otherwise
+    /// the default is 1 (no anti-aliasing)
+    pub fn sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+    /// the resolution of the shadow map of every real light
+    /// the default is 512x512
+    pub fn shadow_resolution(mut self, shadow_resolution: [u32; 2]) -> Self {
+        self.shadow_resolution = shadow_resolution;
+        self
+    }
+    /// the limits requested from the device
+    pub fn limits(mut self, limits: wgpu::Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+    /// the directory the shaders are loaded from and watched in
+    /// this is only used with the hot_reload_shader feature
+    pub fn hot_reload_shader<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.hot_reload_shader = Some(path.into());
+        self
+    }
+}
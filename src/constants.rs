@@ -18,6 +18,10 @@ pub const SHADOW_SIZE: wgpu::Extent3d = wgpu::Extent3d {
     depth: MAX_LIGHTS as u32,
 };*/
 pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+/// the format of the texture rendered into when there is no window
+pub const OFFSCREEN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+/// the corners of the camera facing quads of points and sprites, in the order of a triangle strip
+pub const QUAD_CORNERS: [[f32; 2]; 4] = [[-0.5, -0.5], [0.5, -0.5], [-0.5, 0.5], [0.5, 0.5]];
 
 pub fn generate_matrix(aspect_ratio: f32) -> Matrix4<f32> {
     let mx_projection = cgmath::perspective(Deg(45f32), aspect_ratio, 1.0, 20.0);
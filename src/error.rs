@@ -0,0 +1,122 @@
+use std::fmt;
+use std::path::PathBuf;
+use wgpu_glyph::ab_glyph::InvalidFont;
+
+/// everything that can go wrong while creating or using the renderer
+#[derive(Debug)]
+pub enum RendererError {
+    /// no GPU adapter matches the requested backends and power preference
+    NoAdapter,
+    /// the adapter could not create a device with the requested limits
+    RequestDevice(wgpu::RequestDeviceError),
+    /// the next frame of the swap chain could not be acquired
+    SwapChain(wgpu::SwapChainError),
+    /// a buffer could not be mapped to read it back from the GPU
+    BufferMap(wgpu::BufferAsyncError),
+    /// an image could not be read, decoded or written
+    Image(image::ImageError),
+    /// an .obj file could not be loaded
+    Obj(tobj::LoadError),
+    /// a gltf file could not be loaded
+    Gltf(Box<dyn std::error::Error + Send + Sync>),
+    /// the file extension of a model is not supported
+    UnsupportedFormat(PathBuf),
+    /// the font data is not a valid font
+    InvalidFont(InvalidFont),
+    /// the handle does not point to anything, what it pointed to was probably removed
+    InvalidHandle,
+    /// all real lights are in use
+    TooManyLights(u32),
+    /// the number of samples per pixel has to be 1, 2, 4 or 8
+    UnsupportedSampleCount(u32),
+    /// the surface of an extra window needs a different texture format than the renderer draws in
+    IncompatibleSurface(wgpu::TextureFormat),
+    /// there is no rendered frame to capture, or the pixels read back from the GPU don't fill an image of its size
+    Capture,
+    /// Background::None keeps what is in the target, that only works for an offscreen target with one sample per pixel
+    UnsupportedBackground,
+}
+
+impl fmt::Display for RendererError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RendererError::NoAdapter => write!(f, "no suitable GPU adapter found on the system"),
+            RendererError::RequestDevice(e) => write!(f, "{}", e),
+            RendererError::SwapChain(e) => write!(f, "{}", e),
+            RendererError::BufferMap(e) => write!(f, "{}", e),
+            RendererError::Image(e) => write!(f, "{}", e),
+            RendererError::Obj(e) => write!(f, "failed to load obj: {}", e),
+            RendererError::Gltf(e) => write!(f, "failed to load gltf: {}", e),
+            RendererError::UnsupportedFormat(path) => {
+                write!(f, "format of {:?} is not supported", path)
+            }
+            RendererError::InvalidFont(e) => write!(f, "{}", e),
+            RendererError::InvalidHandle => write!(f, "handle does not point to anything"),
+            RendererError::TooManyLights(max) => {
+                write!(f, "maximum number of lights reached ({})", max)
+            }
+            RendererError::UnsupportedSampleCount(count) => {
+                write!(f, "sample count {} is not supported, use 1, 2, 4 or 8", count)
+            }
+            RendererError::IncompatibleSurface(format) => {
+                write!(f, "the window needs the texture format {:?}", format)
+            }
+            RendererError::Capture => write!(f, "there is no rendered frame to capture"),
+            RendererError::UnsupportedBackground => write!(
+                f,
+                "Background::None needs a headless renderer with one sample per pixel"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RendererError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RendererError::RequestDevice(e) => Some(e),
+            RendererError::SwapChain(e) => Some(e),
+            RendererError::BufferMap(e) => Some(e),
+            RendererError::Image(e) => Some(e),
+            RendererError::Obj(e) => Some(e),
+            RendererError::Gltf(e) => Some(e.as_ref()),
+            RendererError::InvalidFont(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for RendererError {
+    fn from(e: wgpu::RequestDeviceError) -> Self {
+        RendererError::RequestDevice(e)
+    }
+}
+
+impl From<wgpu::SwapChainError> for RendererError {
+    fn from(e: wgpu::SwapChainError) -> Self {
+        RendererError::SwapChain(e)
+    }
+}
+
+impl From<wgpu::BufferAsyncError> for RendererError {
+    fn from(e: wgpu::BufferAsyncError) -> Self {
+        RendererError::BufferMap(e)
+    }
+}
+
+impl From<image::ImageError> for RendererError {
+    fn from(e: image::ImageError) -> Self {
+        RendererError::Image(e)
+    }
+}
+
+impl From<tobj::LoadError> for RendererError {
+    fn from(e: tobj::LoadError) -> Self {
+        RendererError::Obj(e)
+    }
+}
+
+impl From<InvalidFont> for RendererError {
+    fn from(e: InvalidFont) -> Self {
+        RendererError::InvalidFont(e)
+    }
+}
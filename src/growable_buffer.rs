@@ -0,0 +1,106 @@
+use crate::Indices;
+use wgpu::util::DeviceExt;
+use wgpu::{Buffer, BufferAddress, BufferSlice, BufferUsage, Device, Queue};
+
+/// a buffer that is written in place while new data fits into it
+/// when it doesn't, the buffer is replaced by one that is at least twice as big
+pub struct GrowableBuffer {
+    buffer: Buffer,
+    capacity: BufferAddress,
+    label: &'static str,
+    usage: BufferUsage,
+}
+
+impl GrowableBuffer {
+    pub(crate) fn new(
+        device: &Device,
+        label: &'static str,
+        usage: BufferUsage,
+        contents: &[u8],
+    ) -> Self {
+        let usage = usage | BufferUsage::COPY_DST;
+        let contents = pad_to_copy_alignment(contents);
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents: &contents,
+            usage,
+        });
+        Self {
+            buffer,
+            capacity: contents.len() as BufferAddress,
+            label,
+            usage,
+        }
+    }
+    /// replace the contents of the buffer
+    /// returns true if a new buffer had to be created
+    pub(crate) fn write(&mut self, device: &Device, queue: &Queue, contents: &[u8]) -> bool {
+        let grow = self.reserve(device, contents.len() as BufferAddress);
+        self.write_at(queue, 0, contents);
+        grow
+    }
+    /// make sure the buffer can hold at least size bytes
+    /// returns true if a new buffer was created, its contents have to be written again
+    pub(crate) fn reserve(&mut self, device: &Device, size: BufferAddress) -> bool {
+        let align_mask = wgpu::COPY_BUFFER_ALIGNMENT - 1;
+        let size = (size + align_mask) & !align_mask;
+        if size <= self.capacity {
+            return false;
+        }
+        self.capacity = size.max(self.capacity * 2);
+        self.buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(self.label),
+            size: self.capacity,
+            usage: self.usage,
+            mapped_at_creation: false,
+        });
+        true
+    }
+    /// write part of the buffer, the offset has to be a multiple of 4 and the contents have to fit
+    pub(crate) fn write_at(&self, queue: &Queue, offset: BufferAddress, contents: &[u8]) {
+        if !contents.is_empty() {
+            queue.write_buffer(&self.buffer, offset, &pad_to_copy_alignment(contents));
+        }
+    }
+    pub fn slice<S: std::ops::RangeBounds<BufferAddress>>(&self, bounds: S) -> BufferSlice<'_> {
+        self.buffer.slice(bounds)
+    }
+}
+
+/// writes to buffers have to be a multiple of 4 bytes long, an odd number of u16 indices is not
+fn pad_to_copy_alignment(contents: &[u8]) -> std::borrow::Cow<'_, [u8]> {
+    let alignment = wgpu::COPY_BUFFER_ALIGNMENT as usize;
+    if contents.len() % alignment == 0 {
+        contents.into()
+    } else {
+        let mut padded = contents.to_vec();
+        padded.resize((contents.len() / alignment + 1) * alignment, 0);
+        padded.into()
+    }
+}
+
+/// write indices into an existing index buffer or create one with the label if there is none yet
+pub(crate) fn write_indices(
+    device: &Device,
+    queue: &Queue,
+    index_buf: &mut Option<GrowableBuffer>,
+    label: &'static str,
+    indices: Option<&Indices>,
+) {
+    match indices {
+        Some(indices) => match index_buf {
+            Some(buffer) => {
+                buffer.write(device, queue, indices.as_bytes());
+            }
+            None => {
+                *index_buf = Some(GrowableBuffer::new(
+                    device,
+                    label,
+                    BufferUsage::INDEX,
+                    indices.as_bytes(),
+                ));
+            }
+        },
+        None => *index_buf = None,
+    }
+}
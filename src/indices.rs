@@ -0,0 +1,126 @@
+use std::ops::Range;
+
+/// the indices of a ColorMesh or UvMesh
+/// 16 bit indices use less memory, meshes with more than 65535 vertices need 32 bit indices
+#[derive(Debug, Clone)]
+pub enum Indices {
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
+
+impl Indices {
+    pub fn len(&self) -> usize {
+        match self {
+            Indices::U16(indices) => indices.len(),
+            Indices::U32(indices) => indices.len(),
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// get the index at position i as u32, whatever the width of the indices is
+    pub fn get(&self, i: usize) -> Option<u32> {
+        match self {
+            Indices::U16(indices) => indices.get(i).map(|index| *index as u32),
+            Indices::U32(indices) => indices.get(i).copied(),
+        }
+    }
+    /// 16 bit indices if they can reach every vertex, 32 bit otherwise
+    /// the largest index of a format restarts strips, so a vertex can't have the index 65535 in 16 bits
+    pub(crate) fn smallest(indices: Vec<u32>, vertex_count: usize) -> Self {
+        if vertex_count <= u16::MAX as usize {
+            Indices::U16(indices.into_iter().map(|index| index as u16).collect())
+        } else {
+            Indices::U32(indices)
+        }
+    }
+    /// the format the index buffer has to be bound with
+    pub(crate) fn format(&self) -> wgpu::IndexFormat {
+        match self {
+            Indices::U16(_) => wgpu::IndexFormat::Uint16,
+            Indices::U32(_) => wgpu::IndexFormat::Uint32,
+        }
+    }
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        match self {
+            Indices::U16(indices) => bytemuck::cast_slice(indices),
+            Indices::U32(indices) => bytemuck::cast_slice(indices),
+        }
+    }
+}
+
+/// the part of a mesh that is drawn, its indices if it has any and its vertices in order otherwise
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum DrawRange {
+    Indexed(Range<u32>),
+    Vertices(Range<u32>),
+}
+
+impl DrawRange {
+    /// index_count is None for a mesh without an index buffer
+    pub(crate) fn new(vertex_count: usize, index_count: Option<usize>) -> Self {
+        match index_count {
+            Some(index_count) => DrawRange::Indexed(0..index_count as u32),
+            None => DrawRange::Vertices(0..vertex_count as u32),
+        }
+    }
+    /// draw the instances, the index buffer of an indexed mesh has to be bound already
+    pub(crate) fn draw(&self, pass: &mut wgpu::RenderPass<'_>, instances: Range<u32>) {
+        match self {
+            DrawRange::Indexed(indices) => pass.draw_indexed(indices.clone(), 0, instances),
+            DrawRange::Vertices(vertices) => pass.draw(vertices.clone(), instances),
+        }
+    }
+}
+
+impl From<Vec<u16>> for Indices {
+    fn from(indices: Vec<u16>) -> Self {
+        Indices::U16(indices)
+    }
+}
+
+impl From<Vec<u32>> for Indices {
+    fn from(indices: Vec<u32>) -> Self {
+        Indices::U32(indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smallest_uses_16_bits_while_no_vertex_has_the_restart_index() {
+        let indices = vec![0, 1, 2];
+        let largest_u16 = u16::MAX as usize;
+        assert_eq!(
+            Indices::smallest(indices.clone(), largest_u16).format(),
+            wgpu::IndexFormat::Uint16
+        );
+        assert_eq!(
+            Indices::smallest(indices, largest_u16 + 1).format(),
+            wgpu::IndexFormat::Uint32
+        );
+    }
+
+    #[test]
+    fn smallest_keeps_the_indices() {
+        let indices = Indices::smallest(vec![2, 0, 1], 3);
+        assert_eq!(indices.len(), 3);
+        assert_eq!(
+            (0..3).map(|i| indices.get(i)).collect::<Vec<_>>(),
+            vec![Some(2), Some(0), Some(1)]
+        );
+    }
+
+    #[test]
+    fn meshes_without_indices_draw_every_vertex() {
+        assert_eq!(DrawRange::new(6, None), DrawRange::Vertices(0..6));
+    }
+
+    #[test]
+    fn meshes_with_indices_draw_every_index() {
+        assert_eq!(DrawRange::new(4, Some(6)), DrawRange::Indexed(0..6));
+        assert_eq!(DrawRange::new(4, Some(0)), DrawRange::Indexed(0..0));
+    }
+}
@@ -0,0 +1,267 @@
+use crate::growable_buffer::GrowableBuffer;
+use crate::slots::{SlotKey, Slots};
+use std::ops::{Deref, Index, IndexMut, Range};
+use wgpu::{BufferAddress, Device, Queue};
+
+/// identifies an instance of a mesh or model
+/// it stays valid while other instances are added and removed
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InstanceId {
+    key: SlotKey,
+}
+
+/// the instances of a mesh or model
+/// every instance that is changed is remembered, updating the mesh only writes those to the GPU
+/// the instances stay packed for the instance buffer, an InstanceId keeps pointing at its instance when they move
+/// reading works like a slice, changing goes through the methods below
+#[derive(Debug, Clone)]
+pub struct Instances<T> {
+    instances: Vec<T>,
+    dirty: Vec<bool>,
+    /// the key of every instance, in the same order as the instances
+    ids: Vec<SlotKey>,
+    /// where the instance of a key is in the packed instances
+    dense_indices: Slots<usize>,
+}
+
+impl<T> Instances<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            instances: vec![],
+            dirty: vec![],
+            ids: vec![],
+            dense_indices: Slots::new(),
+        }
+    }
+    /// add an instance at the end
+    pub fn insert(&mut self, instance: T) -> InstanceId {
+        let key = self.dense_indices.insert(self.instances.len());
+        self.instances.push(instance);
+        self.dirty.push(true);
+        self.ids.push(key);
+        InstanceId { key }
+    }
+    /// same as insert
+    pub fn push(&mut self, instance: T) -> InstanceId {
+        self.insert(instance)
+    }
+    pub fn pop(&mut self) -> Option<T> {
+        let key = self.ids.pop()?;
+        self.dense_indices.remove(key);
+        self.dirty.pop();
+        self.instances.pop()
+    }
+    /// remove an instance and put the last one in its place
+    /// an id that was removed already is ignored
+    pub fn remove(&mut self, id: InstanceId) -> Option<T> {
+        let index = self.dense_indices.remove(id.key)?;
+        Some(self.swap_remove_packed(index))
+    }
+    /// remove the instance at the index and put the last one in its place
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let key = self.ids[index];
+        self.dense_indices.remove(key);
+        self.swap_remove_packed(index)
+    }
+    /// the key of the instance has to be removed from dense_indices already
+    fn swap_remove_packed(&mut self, index: usize) -> T {
+        self.ids.swap_remove(index);
+        self.dirty.swap_remove(index);
+        if let Some(&moved) = self.ids.get(index) {
+            if let Some(dense_index) = self.dense_indices.get_mut(moved) {
+                *dense_index = index;
+            }
+            self.dirty[index] = true;
+        }
+        self.instances.swap_remove(index)
+    }
+    /// remove all instances, their ids become invalid
+    pub fn clear(&mut self) {
+        self.instances.clear();
+        self.dirty.clear();
+        self.ids.clear();
+        self.dense_indices.clear();
+    }
+    /// the position of the instance in the instance buffer
+    pub fn index_of(&self, id: InstanceId) -> Option<usize> {
+        self.dense_indices.get(id.key).copied()
+    }
+    /// the id of the instance at the index
+    pub fn id_at(&self, index: usize) -> Option<InstanceId> {
+        self.ids.get(index).map(|&key| InstanceId { key })
+    }
+    pub fn contains(&self, id: InstanceId) -> bool {
+        self.index_of(id).is_some()
+    }
+    pub fn get(&self, id: InstanceId) -> Option<&T> {
+        let index = self.index_of(id)?;
+        self.instances.get(index)
+    }
+    /// get an instance to change it
+    pub fn get_mut(&mut self, id: InstanceId) -> Option<&mut T> {
+        let index = self.index_of(id)?;
+        self.dirty[index] = true;
+        self.instances.get_mut(index)
+    }
+    /// all instances together with their ids, in the order of the instance buffer
+    pub fn iter_with_ids(&self) -> impl Iterator<Item = (InstanceId, &T)> {
+        self.ids
+            .iter()
+            .map(|&key| InstanceId { key })
+            .zip(self.instances.iter())
+    }
+    /// change all instances
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.mark_from(0);
+        self.instances.iter_mut()
+    }
+    /// mark an instance as changed without touching it
+    pub fn mark_dirty(&mut self, index: usize) {
+        if let Some(dirty) = self.dirty.get_mut(index) {
+            *dirty = true;
+        }
+    }
+    fn mark_from(&mut self, index: usize) {
+        for dirty in self.dirty.iter_mut().skip(index) {
+            *dirty = true;
+        }
+    }
+    /// the ranges of instances that changed since the last call, neighbouring instances are merged
+    fn take_dirty_ranges(&mut self) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = vec![];
+        for (index, dirty) in self.dirty.iter_mut().enumerate() {
+            if !*dirty {
+                continue;
+            }
+            *dirty = false;
+            match ranges.last_mut() {
+                Some(range) if range.end == index => range.end += 1,
+                _ => ranges.push(index..index + 1),
+            }
+        }
+        ranges
+    }
+    /// write the instances that changed into the instance buffer and return the ranges that were written
+    /// everything is written when the buffer had to grow
+    pub(crate) fn upload<R: bytemuck::Pod>(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        buffer: &mut GrowableBuffer,
+        to_raw: impl Fn(&T) -> R,
+    ) -> Vec<Range<usize>> {
+        let stride = std::mem::size_of::<R>();
+        let grown = buffer.reserve(device, (self.instances.len() * stride) as BufferAddress);
+        let mut ranges = self.take_dirty_ranges();
+        if grown {
+            ranges = vec![0..self.instances.len()];
+        }
+        for range in &ranges {
+            let raw = self.instances[range.clone()]
+                .iter()
+                .map(&to_raw)
+                .collect::<Vec<R>>();
+            buffer.write_at(
+                queue,
+                (range.start * stride) as BufferAddress,
+                bytemuck::cast_slice(&raw),
+            );
+        }
+        ranges
+    }
+}
+
+impl<T> Default for Instances<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Deref for Instances<T> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        &self.instances
+    }
+}
+
+impl<T> Index<InstanceId> for Instances<T> {
+    type Output = T;
+    fn index(&self, id: InstanceId) -> &T {
+        self.get(id).expect("no instance with this id")
+    }
+}
+
+/// changing an instance through an id marks it as changed
+impl<T> IndexMut<InstanceId> for Instances<T> {
+    fn index_mut(&mut self, id: InstanceId) -> &mut T {
+        self.get_mut(id).expect("no instance with this id")
+    }
+}
+
+impl<T> Index<usize> for Instances<T> {
+    type Output = T;
+    fn index(&self, index: usize) -> &T {
+        &self.instances[index]
+    }
+}
+
+/// changing an instance through an index marks it as changed
+impl<T> IndexMut<usize> for Instances<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.dirty[index] = true;
+        &mut self.instances[index]
+    }
+}
+
+impl<T> Extend<T> for Instances<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for instance in iter {
+            self.push(instance);
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Instances<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.instances.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remove_from_the_middle_keeps_ids_pointing_at_their_instances() {
+        let mut instances = Instances::new();
+        let ids: Vec<InstanceId> = (0..10_000).map(|value| instances.insert(value)).collect();
+        for &id in &ids[2_500..7_500] {
+            assert!(instances.remove(id).is_some());
+        }
+        assert_eq!(instances.len(), 5_000);
+        for (value, &id) in ids.iter().enumerate() {
+            let removed = (2_500..7_500).contains(&value);
+            assert_eq!(
+                instances.get_mut(id).copied(),
+                Some(value).filter(|_| !removed)
+            );
+            if let Some(index) = instances.index_of(id) {
+                assert_eq!(instances[index], value);
+                assert_eq!(instances.id_at(index), Some(id));
+            }
+        }
+    }
+
+    #[test]
+    fn clear_invalidates_every_id() {
+        let mut instances = Instances::new();
+        let ids: Vec<InstanceId> = (0..100).map(|value| instances.insert(value)).collect();
+        instances.clear();
+        let new_id = instances.insert(100);
+        assert_eq!(instances.len(), 1);
+        assert!(ids.iter().all(|&id| !instances.contains(id)));
+        assert_eq!(instances[new_id], 100);
+    }
+}
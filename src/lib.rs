@@ -4,90 +4,185 @@
 
 use crate::api::lights::{RealLight, SimpleLight};
 use color_mesh::ColorMesh;
+use cgmath::Point3;
 use constants::*;
 use lines::Lines;
 use model::Model;
+use point_cloud::PointCloud;
 use new::New;
 use render_passes::shader_compiler::ShaderCompiler;
 use render_passes::Passes;
-use std::path::PathBuf;
+use render_target::{ForwardTextures, RenderTarget};
+use slots::{SlotKey, Slots};
+use sprites::Sprite;
 use uniforms::GlobalUniforms;
 use update::Update;
 use uv_mesh::UvModel;
-use wgpu::{Buffer, SwapChain, SwapChainDescriptor, Texture};
-use wgpu_glyph::ab_glyph::InvalidFont;
+use wgpu::{Buffer, SwapChainDescriptor, Texture};
 use wgpu_glyph::{ab_glyph, FontId};
 
 pub use api::lights::LightAttenuation;
 pub use api::lights::RealLightApi;
+pub use api::lights::RealLightHandle;
 pub use api::lights::RealLightPublic;
 pub use api::meshes::MeshApi;
+pub use api::picking::PickApi;
+pub use api::picking::PickHit;
+pub use api::picking::PickTarget;
+pub use api::point_clouds::PointCloudApi;
+pub use api::point_clouds::PointCloudHandle;
+pub use api::render_textures::RenderTextureApi;
+pub use api::sprites::SpriteApi;
+pub use api::sprites::SpriteHandle;
+pub use api::viewports::ViewportApi;
+pub use api::windows::WindowApi;
+pub use background::Background;
+pub use bounds::Aabb;
+pub use bounds::BoundingSphere;
+pub use bounds::Bounds;
+pub use bounds::Frustum;
 pub use camera::Camera;
+pub use capture::Capture;
 pub use camera::ViewMatrixMode;
+pub use config::RendererConfig;
+pub use error::RendererError;
+pub use indices::Indices;
+pub use instances::InstanceId;
+pub use instances::Instances;
 pub use cgmath;
 pub use color_mesh::ColorMeshInstance;
 pub use color_mesh::ColorVertex;
 pub use color_mesh::Lighting;
 pub use lines::Line;
 pub use lines::LineVertex;
+pub use mesh_utils::compute_tangents;
+pub use mesh_utils::flat_normals;
+pub use mesh_utils::merge;
+pub use mesh_utils::smooth_normals;
+pub use mesh_utils::weld;
+pub use mesh_utils::MeshVertex;
 pub use model::ModelVertex;
+pub use picking::Ray;
+pub use point_cloud::Point;
+pub use point_cloud::PointSize;
+pub use primitives::Primitive;
 pub use render::Render;
+pub use render_state::RenderState;
+pub use render_texture::RenderTexture;
+pub use render_texture::RenderTextureHandle;
 pub use resize::Resize;
+pub use shapes::Shape;
+pub use shapes::ShapeKind;
+pub use shapes::ShapeStyle;
+pub use sprites::Billboard;
+pub use sprites::SpriteInstance;
 pub use text::Paragraph;
 pub use text::TextSection;
 pub use transform::Transform;
+pub use transparency::BlendMode;
 pub use uv_mesh::UvVertex;
+pub use viewport::Viewport;
+pub use viewport::ViewportHandle;
+pub use viewport::ALL_LAYERS;
+pub use viewport::DEFAULT_LAYER;
+pub use window::ExtraWindow;
+pub use window::WindowHandle;
 pub use wgpu_glyph::{HorizontalAlign, VerticalAlign};
 pub use api::meshes::ColorMeshHandle;
 pub use api::meshes::UvMeshHandle;
 pub use api::meshes::ModelHandle;
 
 mod api;
+mod background;
+mod bounds;
 mod camera;
+mod capture;
+mod config;
 mod color_mesh;
 mod constants;
+mod error;
+mod growable_buffer;
+mod indices;
 mod instance;
+mod instances;
 mod lines;
+mod mesh_utils;
 mod model;
 mod new;
+mod picking;
+mod point_cloud;
+mod primitives;
 mod render;
 mod render_passes;
+mod render_state;
+mod render_target;
+mod render_texture;
 mod resize;
+mod shapes;
+mod slots;
+mod sprites;
 mod text;
 mod texture;
 mod transform;
+mod transparency;
 mod uniforms;
 mod update;
 mod uv_mesh;
+mod viewport;
+mod window;
 
 /// This contains the State of the Renderer
 pub struct WgpuRenderer {
-    _instance: wgpu::Instance,
-    _adapter: wgpu::Adapter,
-    surface: wgpu::Surface,
+    instance: wgpu::Instance,
+    adapter: wgpu::Adapter,
     device: wgpu::Device,
     queue: wgpu::Queue,
     sc_desc: SwapChainDescriptor,
-    swap_chain: SwapChain,
+    target: RenderTarget,
+    /// a frame was drawn into the offscreen target since it was created, so it can be captured
+    frame_rendered: bool,
+    /// the next frame rendered into the window is also copied into captured_frame
+    capture_requested: bool,
+    captured_frame: Option<image::RgbaImage>,
     global_uniforms: GlobalUniforms,
     real_lights: Vec<RealLight>,
     lights_are_dirty: bool,
 
-    color_meshes: Vec<Option<ColorMesh>>,
-    uv_meshes: Vec<Option<UvModel>>,
-    models: Vec<Option<Model>>,
+    color_meshes: Slots<ColorMesh>,
+    uv_meshes: Slots<UvModel>,
+    models: Slots<Model>,
     lines: Lines,
+    point_clouds: Slots<PointCloud>,
+    sprites: Slots<Sprite>,
 
     passes: Passes,
 
     shadow_texture: Texture,
-    forward_depth: wgpu::TextureView,
+    /// None until the window has an area
+    forward_textures: Option<ForwardTextures>,
     camera: Camera,
+    main_viewport_rect: [f32; 4],
+    main_layer_mask: u32,
+    viewports: Slots<Viewport>,
+    /// the keys of the viewports in the order they were added, which is the order they are drawn in
+    viewport_order: Vec<SlotKey>,
+    render_textures: Slots<RenderTexture>,
+    /// the keys of the render textures in the order they were added, which is the order they are drawn in
+    render_texture_order: Vec<SlotKey>,
+    windows: Slots<ExtraWindow>,
+    /// the GlobalUniforms of the main view followed by those of every viewport, render texture and window
+    view_uniforms_buf: Buffer,
+    views_in_buffer: usize,
+    /// the frustum of every view in view_uniforms_buf, in the same order
+    view_frustums: Vec<Frustum>,
+    /// the camera position of every view in view_uniforms_buf, transparent meshes are sorted by the distance to it
+    view_positions: Vec<Point3<f32>>,
     glyph_brush: wgpu_glyph::GlyphBrush<()>,
     staging_belt: wgpu::util::StagingBelt,
     local_pool: futures::executor::LocalPool,
     local_spawner: futures::executor::LocalSpawner,
     paragraphs: Vec<Paragraph>,
+    shapes: Vec<Shape>,
     simple_lights: Vec<SimpleLight>,
     simple_lights_storage_buffer: Buffer,
     real_lights_storage_buffer: Buffer,
@@ -95,17 +190,30 @@ pub struct WgpuRenderer {
     shadow_sampler: wgpu::Sampler,
     max_real_lights: u32,
     shadow_resolution: [u32; 2],
+    background: Background,
+    background_buf: Buffer,
+    /// the corners of the quads point clouds and sprites are drawn as
+    quad_corners_buf: Buffer,
+    sample_count: u32,
     #[allow(dead_code)]
     shaders: ShaderCompiler,
 }
 
 impl WgpuRenderer {
     /// create the renderer by supplying a Window
-    pub fn new(window: &winit::window::Window, hot_reload_shader: Option<PathBuf>) -> Self {
-        New::new(window, hot_reload_shader)
+    pub fn new(
+        window: &winit::window::Window,
+        config: RendererConfig,
+    ) -> Result<Self, RendererError> {
+        New::new(window, config)
     }
-    pub fn render(&mut self) {
-        Render::render(self);
+    /// create a renderer without a window
+    /// frames are rendered into an offscreen texture of the given size in pixels
+    pub fn new_headless(size: (u32, u32), config: RendererConfig) -> Result<Self, RendererError> {
+        New::new_headless(size, config)
+    }
+    pub fn render(&mut self) -> Result<(), RendererError> {
+        Render::render(self)
     }
     pub fn simple_lights(&mut self) -> &mut Vec<SimpleLight> {
         &mut self.simple_lights
@@ -119,19 +227,51 @@ impl WgpuRenderer {
         Update::update(self);
     }
     /// get the aspect ratio of the window
+    /// this is 1.0 while the window has no area, for example when it is minimized
     pub fn aspect(&self) -> f32 {
-        self.sc_desc.width as f32 / self.sc_desc.height as f32
+        if self.sc_desc.width == 0 || self.sc_desc.height == 0 {
+            1.0
+        } else {
+            self.sc_desc.width as f32 / self.sc_desc.height as f32
+        }
     }
     /// get mutable access to all the paragraphs displayed on screen
     pub fn paragraphs(&mut self) -> &mut Vec<Paragraph> {
         &mut self.paragraphs
     }
+    /// get mutable access to all the 2D shapes displayed on screen
+    pub fn shapes(&mut self) -> &mut Vec<Shape> {
+        &mut self.shapes
+    }
     /// load a font from a static slice of bytes
     /// will fail if the bytes are an invalid font
-    pub fn add_font(&mut self, data: &'static [u8]) -> Result<FontId, InvalidFont> {
+    pub fn add_font(&mut self, data: &'static [u8]) -> Result<FontId, RendererError> {
         let font = ab_glyph::FontArc::try_from_slice(data)?;
         Ok(self.glyph_brush.add_font(font))
     }
+    /// set what the screen is filled with before anything else is drawn
+    /// fails for Background::None unless the renderer is headless and has one sample per pixel
+    pub fn set_background(&mut self, background: Background) -> Result<(), RendererError> {
+        let keeps_target =
+            matches!(self.target, RenderTarget::Texture(_)) && self.sample_count == 1;
+        if background == Background::None && !keeps_target {
+            return Err(RendererError::UnsupportedBackground);
+        }
+        if let Some(vertices) = background.vertices() {
+            self.queue
+                .write_buffer(&self.background_buf, 0, bytemuck::cast_slice(&vertices));
+        }
+        self.background = background;
+        Ok(())
+    }
+    /// get the current background
+    pub fn background(&self) -> Background {
+        self.background
+    }
+    /// clear the screen to a single color before every frame
+    pub fn set_clear_color(&mut self, color: [f64; 4]) {
+        self.background = Background::Color(color);
+    }
     /// This allows turning on/off all lighting calculations in the shaders.
     /// The default is on
     pub fn enable_lighting(&mut self, enabled: bool) {
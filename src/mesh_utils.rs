@@ -0,0 +1,342 @@
+use crate::{ColorVertex, Indices, ModelVertex, Transform, UvVertex};
+use cgmath::{ElementWise, InnerSpace, Vector3, Zero};
+use std::collections::HashMap;
+
+/// a vertex the functions in this module can work on
+pub trait MeshVertex: Copy {
+    fn position(&self) -> Vector3<f32>;
+    fn set_position(&mut self, position: Vector3<f32>);
+    fn normal(&self) -> Vector3<f32>;
+    fn set_normal(&mut self, normal: Vector3<f32>);
+    /// whether everything except the position and the normal is equal within epsilon
+    fn same_attributes(&self, other: &Self, epsilon: f32) -> bool;
+    /// move the vertex by the transform, directions are turned and stay normalized
+    fn transform(&mut self, transform: &Transform) {
+        self.set_position(transform.transform_point(self.position()));
+        self.set_normal(transform_normal(transform, self.normal()));
+    }
+}
+
+impl MeshVertex for ColorVertex {
+    fn position(&self) -> Vector3<f32> {
+        self.get_position()
+    }
+    fn set_position(&mut self, position: Vector3<f32>) {
+        ColorVertex::set_position(self, position);
+    }
+    fn normal(&self) -> Vector3<f32> {
+        self.get_normal()
+    }
+    fn set_normal(&mut self, normal: Vector3<f32>) {
+        ColorVertex::set_normal(self, normal);
+    }
+    fn same_attributes(&self, other: &Self, epsilon: f32) -> bool {
+        let difference = self.get_color() - other.get_color();
+        difference.x.abs() <= epsilon
+            && difference.y.abs() <= epsilon
+            && difference.z.abs() <= epsilon
+            && difference.w.abs() <= epsilon
+    }
+}
+
+impl MeshVertex for UvVertex {
+    fn position(&self) -> Vector3<f32> {
+        self.get_position()
+    }
+    fn set_position(&mut self, position: Vector3<f32>) {
+        UvVertex::set_position(self, position);
+    }
+    fn normal(&self) -> Vector3<f32> {
+        self.get_normal()
+    }
+    fn set_normal(&mut self, normal: Vector3<f32>) {
+        UvVertex::set_normal(self, normal);
+    }
+    fn same_attributes(&self, other: &Self, epsilon: f32) -> bool {
+        let difference = self.get_uv() - other.get_uv();
+        difference.x.abs() <= epsilon && difference.y.abs() <= epsilon
+    }
+}
+
+impl MeshVertex for ModelVertex {
+    fn position(&self) -> Vector3<f32> {
+        self.pos.into()
+    }
+    fn set_position(&mut self, position: Vector3<f32>) {
+        self.pos = position.into();
+    }
+    fn normal(&self) -> Vector3<f32> {
+        self.normal.into()
+    }
+    fn set_normal(&mut self, normal: Vector3<f32>) {
+        self.normal = normal.into();
+    }
+    fn same_attributes(&self, other: &Self, epsilon: f32) -> bool {
+        (self.uv[0] - other.uv[0]).abs() <= epsilon && (self.uv[1] - other.uv[1]).abs() <= epsilon
+    }
+    fn transform(&mut self, transform: &Transform) {
+        self.set_position(transform.transform_point(self.position()));
+        self.set_normal(transform_normal(transform, self.normal()));
+        self.tangent = transform_direction(transform, self.tangent.into()).into();
+        self.bitangent = transform_direction(transform, self.bitangent.into()).into();
+    }
+}
+
+/// give every triangle its own vertices with the normal of the triangle, for a faceted look
+pub fn flat_normals<V: MeshVertex>(vertices: &[V], indices: Option<&Indices>) -> (Vec<V>, Indices) {
+    let mut flat = Vec::with_capacity(vertices.len());
+    for [a, b, c] in triangles(vertices.len(), indices) {
+        let mut corners = [vertices[a], vertices[b], vertices[c]];
+        let normal = face_normal(&corners);
+        if let Some(normal) = normal.filter(|normal| !normal.is_zero()) {
+            for corner in corners.iter_mut() {
+                corner.set_normal(normal.normalize());
+            }
+        }
+        flat.extend_from_slice(&corners);
+    }
+    let indices = Indices::smallest((0..flat.len() as u32).collect(), flat.len());
+    (flat, indices)
+}
+
+/// set the normal of every vertex to the average of the triangles around it
+/// each triangle counts as much as the angle of its corner at the vertex
+/// vertices are only shared by the triangles that use the same index, weld them first to smooth over seams
+pub fn smooth_normals<V: MeshVertex>(vertices: &mut [V], indices: Option<&Indices>) {
+    let mut normals = vec![Vector3::zero(); vertices.len()];
+    for triangle in triangles(vertices.len(), indices) {
+        let corners = [
+            vertices[triangle[0]],
+            vertices[triangle[1]],
+            vertices[triangle[2]],
+        ];
+        let normal = match face_normal(&corners) {
+            Some(normal) if !normal.is_zero() => normal.normalize(),
+            _ => continue,
+        };
+        for corner in 0..3 {
+            let position = corners[corner].position();
+            let to_next = corners[(corner + 1) % 3].position() - position;
+            let to_previous = corners[(corner + 2) % 3].position() - position;
+            if to_next.is_zero() || to_previous.is_zero() {
+                continue;
+            }
+            let angle = to_next
+                .normalize()
+                .dot(to_previous.normalize())
+                .max(-1.0)
+                .min(1.0)
+                .acos();
+            normals[triangle[corner]] += normal * angle;
+        }
+    }
+    for (vertex, normal) in vertices.iter_mut().zip(normals) {
+        if !normal.is_zero() {
+            vertex.set_normal(normal.normalize());
+        }
+    }
+}
+
+/// calculate the tangents and bitangents of every vertex from the uvs of the triangles around it
+/// they are averaged and made perpendicular to the normal
+pub fn compute_tangents(vertices: &mut [ModelVertex], indices: Option<&Indices>) {
+    let triangles = triangles(vertices.len(), indices).collect::<Vec<_>>();
+    tangents_for_triangles(vertices, &triangles);
+}
+
+/// the same as compute_tangents for the u32 indices the loaders read
+pub(crate) fn compute_tangents_u32(vertices: &mut [ModelVertex], indices: &[u32]) {
+    let triangles = indices
+        .chunks_exact(3)
+        .map(|triangle| {
+            [
+                triangle[0] as usize,
+                triangle[1] as usize,
+                triangle[2] as usize,
+            ]
+        })
+        .collect::<Vec<_>>();
+    tangents_for_triangles(vertices, &triangles);
+}
+
+fn tangents_for_triangles(vertices: &mut [ModelVertex], triangles: &[[usize; 3]]) {
+    let mut tangents = vec![Vector3::zero(); vertices.len()];
+    let mut bitangents = vec![Vector3::zero(); vertices.len()];
+    for triangle in triangles {
+        let [v0, v1, v2] = [
+            vertices[triangle[0]],
+            vertices[triangle[1]],
+            vertices[triangle[2]],
+        ];
+        let delta_pos1 = Vector3::from(v1.pos) - Vector3::from(v0.pos);
+        let delta_pos2 = Vector3::from(v2.pos) - Vector3::from(v0.pos);
+        let delta_uv1 = [v1.uv[0] - v0.uv[0], v1.uv[1] - v0.uv[1]];
+        let delta_uv2 = [v2.uv[0] - v0.uv[0], v2.uv[1] - v0.uv[1]];
+
+        // solve delta_pos = delta_uv.x * tangent + delta_uv.y * bitangent for both edges
+        let det = delta_uv1[0] * delta_uv2[1] - delta_uv1[1] * delta_uv2[0];
+        if det.abs() < f32::EPSILON {
+            continue;
+        }
+        let tangent = (delta_pos1 * delta_uv2[1] - delta_pos2 * delta_uv1[1]) / det;
+        let bitangent = (delta_pos2 * delta_uv1[0] - delta_pos1 * delta_uv2[0]) / det;
+        for &index in triangle {
+            tangents[index] += tangent;
+            bitangents[index] += bitangent;
+        }
+    }
+    for (i, vertex) in vertices.iter_mut().enumerate() {
+        let normal = Vector3::from(vertex.normal);
+        let (tangent, bitangent) = orthonormal_tangent(normal, tangents[i], bitangents[i]);
+        vertex.tangent = tangent.into();
+        vertex.bitangent = bitangent.into();
+    }
+}
+
+/// merge vertices that are closer than epsilon to each other and have the same attributes
+/// the merged vertex keeps the normal of the first one, use smooth_normals afterwards to average them
+pub fn weld<V: MeshVertex>(
+    vertices: &[V],
+    indices: Option<&Indices>,
+    epsilon: f32,
+) -> (Vec<V>, Indices) {
+    let cell_size = epsilon.max(f32::EPSILON);
+    let cell = |position: Vector3<f32>| {
+        [
+            (position.x / cell_size).floor() as i64,
+            (position.y / cell_size).floor() as i64,
+            (position.z / cell_size).floor() as i64,
+        ]
+    };
+    let mut welded: Vec<V> = vec![];
+    let mut cells: HashMap<[i64; 3], Vec<u32>> = HashMap::new();
+    let mut remap = Vec::with_capacity(vertices.len());
+    for vertex in vertices {
+        let [x, y, z] = cell(vertex.position());
+        let mut found = None;
+        'search: for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    for &candidate in cells.get(&[x + dx, y + dy, z + dz]).into_iter().flatten() {
+                        let other = &welded[candidate as usize];
+                        if (other.position() - vertex.position()).magnitude() <= epsilon
+                            && other.same_attributes(vertex, epsilon)
+                        {
+                            found = Some(candidate);
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+        let index = match found {
+            Some(index) => index,
+            None => {
+                welded.push(*vertex);
+                let index = welded.len() as u32 - 1;
+                cells.entry([x, y, z]).or_default().push(index);
+                index
+            }
+        };
+        remap.push(index);
+    }
+    let indices = triangles(vertices.len(), indices)
+        .flat_map(|triangle| triangle.to_vec())
+        .map(|index| remap[index])
+        .collect();
+    let indices = Indices::smallest(indices, welded.len());
+    (welded, indices)
+}
+
+/// put several meshes into one, every mesh is moved by its transform first
+pub fn merge<'a, V: MeshVertex + 'a>(
+    meshes: impl IntoIterator<Item = (&'a [V], Option<&'a Indices>, Transform)>,
+) -> (Vec<V>, Indices) {
+    let mut merged = vec![];
+    let mut merged_indices = vec![];
+    for (vertices, indices, transform) in meshes {
+        let first = merged.len() as u32;
+        merged.extend(vertices.iter().map(|vertex| {
+            let mut vertex = *vertex;
+            vertex.transform(&transform);
+            vertex
+        }));
+        merged_indices.extend(
+            triangles(vertices.len(), indices)
+                .flat_map(|triangle| triangle.to_vec())
+                .map(|index| first + index as u32),
+        );
+    }
+    let indices = Indices::smallest(merged_indices, merged.len());
+    (merged, indices)
+}
+
+/// the corners of every triangle, without indices every three vertices are a triangle
+pub(crate) fn triangles<'a>(
+    vertex_count: usize,
+    indices: Option<&'a Indices>,
+) -> impl Iterator<Item = [usize; 3]> + Clone + 'a {
+    let count = indices.map_or(vertex_count, |indices| indices.len()) / 3;
+    (0..count).map(move |triangle| {
+        let corner = |i: usize| match indices {
+            Some(indices) => indices.get(triangle * 3 + i).unwrap_or(0) as usize,
+            None => triangle * 3 + i,
+        };
+        [corner(0), corner(1), corner(2)]
+    })
+}
+
+/// the not normalized normal of a counter clockwise triangle, None if a corner is not a number
+fn face_normal<V: MeshVertex>(corners: &[V; 3]) -> Option<Vector3<f32>> {
+    let normal = (corners[1].position() - corners[0].position())
+        .cross(corners[2].position() - corners[0].position());
+    if normal.x.is_finite() && normal.y.is_finite() && normal.z.is_finite() {
+        Some(normal)
+    } else {
+        None
+    }
+}
+
+/// make the tangent perpendicular to the normal and the bitangent perpendicular to both
+/// the bitangent keeps the side it was on, so mirrored uvs stay mirrored
+fn orthonormal_tangent(
+    normal: Vector3<f32>,
+    tangent: Vector3<f32>,
+    bitangent: Vector3<f32>,
+) -> (Vector3<f32>, Vector3<f32>) {
+    let mut tangent = tangent - normal * normal.dot(tangent);
+    if tangent.magnitude2() < f32::EPSILON {
+        // there are no uvs to follow, any direction along the surface will do
+        let axis = if normal.x.abs() < 0.9 {
+            Vector3::unit_x()
+        } else {
+            Vector3::unit_y()
+        };
+        tangent = axis - normal * normal.dot(axis);
+    }
+    let tangent = tangent.normalize();
+    let mut cross = normal.cross(tangent);
+    if cross.dot(bitangent) < 0.0 {
+        cross = -cross;
+    }
+    (tangent, cross)
+}
+
+/// normals are scaled by the inverse of the scale so they stay perpendicular to the surface
+fn transform_normal(transform: &Transform, normal: Vector3<f32>) -> Vector3<f32> {
+    let normal = transform.rotation * normal.div_element_wise(transform.scale);
+    if normal.is_zero() {
+        normal
+    } else {
+        normal.normalize()
+    }
+}
+
+fn transform_direction(transform: &Transform, direction: Vector3<f32>) -> Vector3<f32> {
+    let direction = transform.rotation * direction.mul_element_wise(transform.scale);
+    if direction.is_zero() {
+        direction
+    } else {
+        direction.normalize()
+    }
+}
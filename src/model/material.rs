@@ -11,23 +11,21 @@ impl Material {
         queue: &Queue,
         diffuse_texture: P,
         normal_texture: P,
-    ) -> Self {
+    ) -> Result<Self, RendererError> {
         let diffuse_texture = Texture::load(
             device,
             queue,
             diffuse_texture,
             wgpu::FilterMode::Nearest,
             wgpu::FilterMode::Nearest,
-        )
-        .unwrap();
+        )?;
         let normal_texture = Texture::load(
             device,
             queue,
             normal_texture,
             wgpu::FilterMode::Linear,
             wgpu::FilterMode::Linear,
-        )
-        .unwrap();
+        )?;
         let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("diffuse bind group"),
             layout: &Self::layout(device),
@@ -50,11 +48,11 @@ impl Material {
                 },
             ],
         });
-        Self {
+        Ok(Self {
             diffuse_texture,
             bind_group,
             normal_texture,
-        }
+        })
     }
     pub fn from_textures(
         device: &Device,
@@ -1,4 +1,5 @@
 use super::*;
+use crate::bounds::Bounds;
 
 pub struct ModelMesh {
     pub vertices: Vec<ModelVertex>,
@@ -9,6 +10,10 @@ pub struct ModelMesh {
     pub index_count: usize,
 
     pub material: usize,
+    /// the box and sphere around the vertices, None if there are no vertices
+    pub bounds: Option<Bounds>,
+    /// the bounds of every instance of the model in its instance buffer, used to skip instances no camera sees
+    pub(crate) instance_bounds: Vec<Bounds>,
 }
 
 impl ModelMesh {
@@ -30,6 +35,8 @@ impl ModelMesh {
         });
 
         Self {
+            bounds: Bounds::from_points(vertices.iter().map(|vertex| vertex.pos.into())),
+            instance_bounds: vec![],
             vertices,
             indices,
             vertex_buffer,
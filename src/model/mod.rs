@@ -1,9 +1,11 @@
+use crate::bounds::update_instance_bounds;
+use crate::growable_buffer::GrowableBuffer;
 use crate::instance::InstanceRaw;
+use crate::mesh_utils::compute_tangents_u32;
 use crate::texture::{create_colored, Texture};
-use crate::{texture, Transform};
+use crate::{texture, Instances, RenderState, RendererError, Transform, DEFAULT_LAYER};
 use std::path::Path;
-use wgpu::util::{BufferInitDescriptor, DeviceExt};
-use wgpu::{BindGroup, BindGroupLayout, Buffer, BufferDescriptor, BufferUsage, Device, Queue};
+use wgpu::{BufferUsage, Device, Queue};
 
 mod material;
 mod mesh;
@@ -18,9 +20,13 @@ use image::buffer::ConvertBuffer;
 pub struct Model {
     pub meshes: Vec<ModelMesh>,
     pub materials: Vec<Material>,
-    pub instance_buffer: Buffer,
-    pub instances: Vec<Transform>,
+    pub instance_buffer: GrowableBuffer,
+    pub instances: Instances<Transform>,
     instances_in_buffer: usize,
+    /// the viewport layers this model is drawn in
+    pub layers: u32,
+    /// how the triangles of all meshes of the model are drawn
+    pub render_state: RenderState,
 }
 
 impl Model {
@@ -29,13 +35,9 @@ impl Model {
         meshes: Vec<ModelMesh>,
         materials: Vec<Material>,
     ) -> Self {
-        let instances = vec![];
-        let instance_buffer = device.create_buffer(&BufferDescriptor {
-            label: Some("model instance buffer"),
-            size: 0,
-            usage: BufferUsage::VERTEX,
-            mapped_at_creation: false,
-        });
+        let instances = Instances::new();
+        let instance_buffer =
+            GrowableBuffer::new(device, "instance vertex buffer", BufferUsage::VERTEX, &[]);
 
         Self {
             meshes,
@@ -43,21 +45,27 @@ impl Model {
             instance_buffer,
             instances_in_buffer: instances.len(),
             instances,
+            layers: DEFAULT_LAYER,
+            render_state: RenderState::default(),
         }
     }
-    pub fn update(&mut self, device: &Device) {
-        self.instance_buffer = device.create_buffer_init(&BufferInitDescriptor {
-            label: Some("instance vertex buffer"),
-            contents: bytemuck::cast_slice(
-                &self
-                    .instances
-                    .iter()
-                    .map(|transform: &Transform| transform.into())
-                    .collect::<Vec<InstanceRaw>>(),
-            ),
-            usage: BufferUsage::VERTEX,
-        });
+    /// write the instances that changed since the last update to the GPU
+    pub fn update(&mut self, device: &Device, queue: &Queue) {
+        let changed =
+            self.instances
+                .upload(device, queue, &mut self.instance_buffer, InstanceRaw::from);
         self.instances_in_buffer = self.instances.len();
+        for mesh in &mut self.meshes {
+            match &mesh.bounds {
+                Some(bounds) => update_instance_bounds(
+                    &mut mesh.instance_bounds,
+                    &self.instances,
+                    &changed,
+                    |transform| bounds.transformed(transform),
+                ),
+                None => mesh.instance_bounds.clear(),
+            }
+        }
     }
     pub fn is_empty(&self) -> bool {
         self.instances_in_buffer == 0
@@ -65,8 +73,12 @@ impl Model {
     pub fn instances_in_buffer(&self) -> usize {
         self.instances_in_buffer
     }
-    pub fn load_gltf<P: AsRef<Path>>(device: &wgpu::Device, queue: &wgpu::Queue, path: P) -> Self {
-        let scenes = easy_gltf::load(path).unwrap();
+    pub fn load_gltf<P: AsRef<Path>>(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        path: P,
+    ) -> Result<Self, RendererError> {
+        let scenes = easy_gltf::load(path).map_err(RendererError::Gltf)?;
 
         let start = std::time::Instant::now();
         let mut meshes = vec![];
@@ -97,21 +109,7 @@ impl Model {
                     .collect();
                 let indices: Vec<u32> =
                     model.indices().unwrap().iter().map(|a| *a as u32).collect();
-                for c in indices.chunks(3) {
-                    let v0 = vertices[c[0] as usize];
-                    let v1 = vertices[c[1] as usize];
-                    let v2 = vertices[c[2] as usize];
-
-                    let (tangent, bitangent) = calculate_tangent_bitangent([v0, v1, v2]);
-
-                    vertices[c[0] as usize].tangent = tangent.into();
-                    vertices[c[1] as usize].tangent = tangent.into();
-                    vertices[c[2] as usize].tangent = tangent.into();
-
-                    vertices[c[0] as usize].bitangent = bitangent.into();
-                    vertices[c[1] as usize].bitangent = bitangent.into();
-                    vertices[c[2] as usize].bitangent = bitangent.into();
-                }
+                compute_tangents_u32(&mut vertices, &indices);
                 let material = model.material();
                 materials.push(Material::from_textures(
                     device,
@@ -179,13 +177,17 @@ impl Model {
                 ));
             }
         }
-        Self::from_mesh_and_materials(device, meshes, materials)
+        Ok(Self::from_mesh_and_materials(device, meshes, materials))
     }
-    pub fn load<P: AsRef<Path>>(device: &wgpu::Device, queue: &wgpu::Queue, path: P) -> Self {
-        let (obj_models, obj_materials) = tobj::load_obj(path.as_ref(), true).unwrap();
+    pub fn load<P: AsRef<Path>>(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        path: P,
+    ) -> Result<Self, RendererError> {
+        let (obj_models, obj_materials) = tobj::load_obj(path.as_ref(), true)?;
 
         // We're assuming that the texture files are stored with the obj file
-        let containing_folder = path.as_ref().parent().unwrap();
+        let containing_folder = path.as_ref().parent().unwrap_or_else(|| Path::new(""));
 
         let mut materials = Vec::new();
         for mat in obj_materials {
@@ -198,7 +200,7 @@ impl Model {
                 queue,
                 containing_folder.join(diffuse_path),
                 containing_folder.join(normal_path),
-            ));
+            )?);
         }
         if materials.is_empty() {
             materials.push(Material::from_textures(
@@ -246,54 +248,7 @@ impl Model {
 
             let indices = m.mesh.indices;
 
-            // Calculate tangents and bitangets. We're going to
-            // use the triangles, so we need to loop through the
-            // indices in chunks of 3
-            for c in indices.chunks(3) {
-                let v0 = vertices[c[0] as usize];
-                let v1 = vertices[c[1] as usize];
-                let v2 = vertices[c[2] as usize];
-
-                let (tangent, bitangent) = calculate_tangent_bitangent([v0, v1, v2]);
-
-                /* let pos0: cgmath::Vector3<f32> = v0.pos.into();
-                let pos1: cgmath::Vector3<f32> = v1.pos.into();
-                let pos2: cgmath::Vector3<f32> = v2.pos.into();
-
-                let uv0: cgmath::Vector2<f32> = v0.uv.into();
-                let uv1: cgmath::Vector2<f32> = v1.uv.into();
-                let uv2: cgmath::Vector2<f32> = v2.uv.into();
-
-                // Calculate the edges of the triangle
-                let delta_pos1 = pos1 - pos0;
-                let delta_pos2 = pos2 - pos0;
-
-                // This will give us a direction to calculate the
-                // tangent and bitangent
-                let delta_uv1 = uv1 - uv0;
-                let delta_uv2 = uv2 - uv0;
-
-                // Solving the following system of equations will
-                // give us the tangent and bitangent.
-                //     delta_pos1 = delta_uv1.x * T + delta_u.y * B
-                //     delta_pos2 = delta_uv2.x * T + delta_uv2.y * B
-                // Luckily, the place I found this equation provided
-                // the solution!
-                let r = 1.0 / (delta_uv1.x * delta_uv2.y - delta_uv1.y * delta_uv2.x);
-                let tangent = (delta_pos1 * delta_uv2.y - delta_pos2 * delta_uv1.y) * r;
-                let bitangent = (delta_pos2 * delta_uv1.x - delta_pos1 * delta_uv2.x) * r;
-
-                // We'll use the same tangent/bitangent for each vertex in the triangle
-                */
-
-                vertices[c[0] as usize].tangent = tangent.into();
-                vertices[c[1] as usize].tangent = tangent.into();
-                vertices[c[2] as usize].tangent = tangent.into();
-
-                vertices[c[0] as usize].bitangent = bitangent.into();
-                vertices[c[1] as usize].bitangent = bitangent.into();
-                vertices[c[2] as usize].bitangent = bitangent.into();
-            }
+            compute_tangents_u32(&mut vertices, &indices);
 
             meshes.push(ModelMesh::new(
                 device,
@@ -303,41 +258,6 @@ impl Model {
             ));
         }
 
-        Self::from_mesh_and_materials(device, meshes, materials)
+        Ok(Self::from_mesh_and_materials(device, meshes, materials))
     }
 }
-
-fn calculate_tangent_bitangent(vertices: [ModelVertex; 3]) -> (Vector3<f32>, Vector3<f32>) {
-    let v0 = vertices[0];
-    let v1 = vertices[1];
-    let v2 = vertices[2];
-
-    let pos0: cgmath::Vector3<f32> = v0.pos.into();
-    let pos1: cgmath::Vector3<f32> = v1.pos.into();
-    let pos2: cgmath::Vector3<f32> = v2.pos.into();
-
-    let uv0: cgmath::Vector2<f32> = v0.uv.into();
-    let uv1: cgmath::Vector2<f32> = v1.uv.into();
-    let uv2: cgmath::Vector2<f32> = v2.uv.into();
-
-    // Calculate the edges of the triangle
-    let delta_pos1 = pos1 - pos0;
-    let delta_pos2 = pos2 - pos0;
-
-    // This will give us a direction to calculate the
-    // tangent and bitangent
-    let delta_uv1 = uv1 - uv0;
-    let delta_uv2 = uv2 - uv0;
-
-    // Solving the following system of equations will
-    // give us the tangent and bitangent.
-    //     delta_pos1 = delta_uv1.x * T + delta_u.y * B
-    //     delta_pos2 = delta_uv2.x * T + delta_uv2.y * B
-    // Luckily, the place I found this equation provided
-    // the solution!
-    let r = 1.0 / (delta_uv1.x * delta_uv2.y - delta_uv1.y * delta_uv2.x);
-    let tangent = (delta_pos1 * delta_uv2.y - delta_pos2 * delta_uv1.y) * r;
-    let bitangent = (delta_pos2 * delta_uv1.x - delta_pos1 * delta_uv2.x) * r;
-
-    (tangent, bitangent)
-}
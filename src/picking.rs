@@ -0,0 +1,167 @@
+use crate::bounds::{BoundingSphere, Bounds};
+use crate::mesh_utils::MeshVertex;
+use crate::viewport::PixelRect;
+use crate::Transform;
+use cgmath::{InnerSpace, Matrix, Matrix4, SquareMatrix, Vector3, Vector4};
+
+/// a half line in world space
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vector3<f32>,
+    /// the distances of hits are measured in lengths of this direction
+    pub direction: Vector3<f32>,
+}
+
+impl Ray {
+    /// the ray from the near plane through the pixel at x and y of the rect into the scene
+    /// None if the point is outside of the rect or the view projection matrix can't be inverted
+    pub(crate) fn from_screen(
+        view_proj: Matrix4<f32>,
+        rect: PixelRect,
+        x: f32,
+        y: f32,
+    ) -> Option<Self> {
+        let x = (x - rect.x as f32) / rect.width as f32;
+        let y = (y - rect.y as f32) / rect.height as f32;
+        if !(0.0..=1.0).contains(&x) || !(0.0..=1.0).contains(&y) {
+            return None;
+        }
+        let inverse = view_proj.invert()?;
+        let unproject = |depth: f32| {
+            let point = inverse * Vector4::new(x * 2.0 - 1.0, 1.0 - y * 2.0, depth, 1.0);
+            point.truncate() / point.w
+        };
+        // the depth of the clip space of wgpu goes from 0 at the near plane to 1 at the far plane
+        let near = unproject(0.0);
+        let far = unproject(1.0);
+        Some(Self {
+            origin: near,
+            direction: (far - near).normalize(),
+        })
+    }
+    pub fn at(&self, distance: f32) -> Vector3<f32> {
+        self.origin + self.direction * distance
+    }
+    /// the distance at which the ray enters the sphere, 0 if it starts inside of it
+    pub fn hit_sphere(&self, sphere: &BoundingSphere) -> Option<f32> {
+        let to_center = sphere.center - self.origin;
+        let a = self.direction.magnitude2();
+        let b = to_center.dot(self.direction);
+        let c = to_center.magnitude2() - sphere.radius * sphere.radius;
+        let discriminant = b * b - a * c;
+        if a == 0.0 || discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        let exit = (b + root) / a;
+        if exit < 0.0 {
+            return None;
+        }
+        Some(((b - root) / a).max(0.0))
+    }
+    /// intersect the ray with a triangle from both sides with the Möller–Trumbore algorithm
+    /// returns the distance and the weights of the second and third corner
+    pub fn hit_triangle(&self, corners: [Vector3<f32>; 3]) -> Option<(f32, f32, f32)> {
+        let edge1 = corners[1] - corners[0];
+        let edge2 = corners[2] - corners[0];
+        let p = self.direction.cross(edge2);
+        let determinant = edge1.dot(p);
+        if determinant.abs() < f32::EPSILON {
+            // the ray is parallel to the triangle
+            return None;
+        }
+        let inverse = 1.0 / determinant;
+        let to_origin = self.origin - corners[0];
+        let u = to_origin.dot(p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = to_origin.cross(edge1);
+        let v = self.direction.dot(q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let distance = edge2.dot(q) * inverse;
+        if distance < 0.0 {
+            return None;
+        }
+        Some((distance, u, v))
+    }
+}
+
+/// where a ray hit a mesh
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct MeshHit {
+    /// the position of the instance in the instances of the mesh
+    pub instance: usize,
+    pub distance: f32,
+    /// the vertices of the triangle that was hit
+    pub triangle: [usize; 3],
+    pub barycentrics: Vector3<f32>,
+    pub position: Vector3<f32>,
+    pub normal: Vector3<f32>,
+}
+
+/// the closest hit of the ray with any instance of a mesh that is closer than max_distance
+/// instances whose bounding sphere the ray misses are skipped without looking at their triangles
+pub(crate) fn hit_mesh<'a, V, T, I>(
+    ray: &Ray,
+    vertices: &[V],
+    triangles: T,
+    bounds: Option<&Bounds>,
+    transforms: I,
+    max_distance: f32,
+) -> Option<MeshHit>
+where
+    V: MeshVertex,
+    T: Iterator<Item = [usize; 3]> + Clone,
+    I: IntoIterator<Item = &'a Transform>,
+{
+    let bounds = bounds?;
+    let mut closest: Option<MeshHit> = None;
+    for (instance, transform) in transforms.into_iter().enumerate() {
+        let max_distance = closest.map_or(max_distance, |hit| hit.distance);
+        match ray.hit_sphere(&bounds.sphere.transformed(transform)) {
+            Some(distance) if distance <= max_distance => {}
+            _ => continue,
+        }
+        let inverse = match Matrix4::from(transform).invert() {
+            Some(inverse) => inverse,
+            None => continue,
+        };
+        // the distances along the local ray are the same as along the world ray
+        let local = Ray {
+            origin: (inverse * ray.origin.extend(1.0)).truncate(),
+            direction: (inverse * ray.direction.extend(0.0)).truncate(),
+        };
+        for triangle in triangles.clone() {
+            let corners = match (
+                vertices.get(triangle[0]),
+                vertices.get(triangle[1]),
+                vertices.get(triangle[2]),
+            ) {
+                (Some(a), Some(b), Some(c)) => [a.position(), b.position(), c.position()],
+                _ => continue,
+            };
+            let (distance, u, v) = match local.hit_triangle(corners) {
+                Some(hit) => hit,
+                None => continue,
+            };
+            if distance > closest.map_or(max_distance, |hit| hit.distance) {
+                continue;
+            }
+            // normals are moved by the inverse transpose to stay perpendicular to the surface
+            let normal = (corners[1] - corners[0]).cross(corners[2] - corners[0]);
+            let normal = (inverse.transpose() * normal.extend(0.0)).truncate();
+            closest = Some(MeshHit {
+                instance,
+                distance,
+                triangle,
+                barycentrics: Vector3::new(1.0 - u - v, u, v),
+                position: ray.at(distance),
+                normal: normal.normalize(),
+            });
+        }
+    }
+    closest
+}
@@ -0,0 +1,146 @@
+use crate::bounds::{Bounds, Frustum};
+use crate::growable_buffer::GrowableBuffer;
+use crate::point_cloud::Point;
+use crate::DEFAULT_LAYER;
+use bytemuck::{Pod, Zeroable};
+use cgmath::Vector3;
+use wgpu::util::DeviceExt;
+use wgpu::{BindGroup, BindGroupLayout, Buffer, BufferUsage, Device, Queue};
+
+/// the unit of the sizes of the points in a PointCloud
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PointSize {
+    /// every point covers the same number of pixels, no matter how far away it is
+    Pixels,
+    /// the points get smaller with distance like any other mesh
+    World,
+}
+
+impl Default for PointSize {
+    fn default() -> Self {
+        PointSize::Pixels
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct PointCloudUniforms {
+    world_size: u32,
+    lit: u32,
+    _padding: [u32; 2],
+}
+
+pub struct PointCloud {
+    pub point_buf: GrowableBuffer,
+    pub points_in_buffer: usize,
+    /// the viewport layers the cloud is drawn in
+    pub layers: u32,
+    pub size: PointSize,
+    /// multiply the colors with the ambient light and the simple lights
+    pub lit: bool,
+    /// the box and sphere around the centers of the points, None if there are no points
+    pub bounds: Option<Bounds>,
+    /// the largest size of a point, world sized points can stick out of the bounds by half of it
+    max_size: f32,
+    uniform_buf: Buffer,
+    pub bind_group: BindGroup,
+}
+
+impl PointCloud {
+    pub fn new(device: &Device, points: &[Point]) -> Self {
+        let uniforms = PointCloudUniforms {
+            world_size: 0,
+            lit: 0,
+            _padding: [0; 2],
+        };
+        let uniform_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("point cloud uniform buffer"),
+            contents: bytemuck::bytes_of(&uniforms),
+            usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &create_point_cloud_bind_group_layout(device),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buf.as_entire_binding(),
+            }],
+            label: Some("point cloud bind group"),
+        });
+        Self {
+            point_buf: GrowableBuffer::new(
+                device,
+                "point cloud buffer",
+                BufferUsage::VERTEX,
+                bytemuck::cast_slice(points),
+            ),
+            points_in_buffer: points.len(),
+            layers: DEFAULT_LAYER,
+            size: PointSize::default(),
+            lit: false,
+            bounds: Bounds::from_points(points.iter().map(Point::get_position)),
+            max_size: max_size(points),
+            uniform_buf,
+            bind_group,
+        }
+    }
+    /// replace all points, the buffer is reused while they fit into it
+    pub fn update(&mut self, device: &Device, queue: &Queue, points: &[Point]) {
+        self.point_buf
+            .write(device, queue, bytemuck::cast_slice(points));
+        self.points_in_buffer = points.len();
+        self.bounds = Bounds::from_points(points.iter().map(Point::get_position));
+        self.max_size = max_size(points);
+    }
+    /// write the size and lighting settings to the GPU
+    pub fn write_uniforms(&self, queue: &Queue) {
+        let uniforms = PointCloudUniforms {
+            world_size: (self.size == PointSize::World) as u32,
+            lit: self.lit as u32,
+            _padding: [0; 2],
+        };
+        queue.write_buffer(&self.uniform_buf, 0, bytemuck::bytes_of(&uniforms));
+    }
+    /// false if the cloud is completely outside of the frustum
+    /// pixel sized points are only culled by their centers, so they can pop in at the edges of the view
+    pub fn is_visible(&self, frustum: Option<&Frustum>) -> bool {
+        match (frustum, &self.bounds) {
+            (Some(frustum), Some(bounds)) => {
+                let mut bounds = *bounds;
+                if self.size == PointSize::World {
+                    let padding = self.max_size / 2.0;
+                    bounds.aabb.min -= Vector3::new(padding, padding, padding);
+                    bounds.aabb.max += Vector3::new(padding, padding, padding);
+                    bounds.sphere.radius += padding;
+                }
+                frustum.intersects(&bounds)
+            }
+            _ => true,
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.points_in_buffer == 0
+    }
+}
+
+fn max_size(points: &[Point]) -> f32 {
+    points.iter().map(Point::get_size).fold(0.0, f32::max)
+}
+
+/// the settings of a single cloud, the second bind group of the point cloud pipeline
+pub fn create_point_cloud_bind_group_layout(device: &Device) -> BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStage::VERTEX,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: wgpu::BufferSize::new(
+                    std::mem::size_of::<PointCloudUniforms>() as _
+                ),
+            },
+            count: None,
+        }],
+        label: Some("point cloud bind group layout"),
+    })
+}
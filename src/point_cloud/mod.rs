@@ -0,0 +1,5 @@
+pub use cloud::*;
+pub use point::*;
+
+mod cloud;
+mod point;
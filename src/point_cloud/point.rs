@@ -0,0 +1,61 @@
+use cgmath::{Vector3, Vector4};
+use wgpu::{VertexBufferLayout, VertexFormat};
+
+use bytemuck::{Pod, Zeroable};
+
+/// one point of a PointCloud
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct Point {
+    position: [f32; 3],
+    size: f32,
+    color: [f32; 4],
+}
+
+impl Point {
+    /// the size is in pixels or in world units, depending on the PointSize of the cloud
+    pub fn new(position: Vector3<f32>, color: Vector4<f32>, size: f32) -> Self {
+        Self {
+            position: position.into(),
+            size,
+            color: color.into(),
+        }
+    }
+    pub fn get_position(&self) -> Vector3<f32> {
+        self.position.into()
+    }
+    pub fn get_size(&self) -> f32 {
+        self.size
+    }
+    /// the points are instances, every one of them is drawn with all four corners
+    pub fn desc<'a>() -> VertexBufferLayout<'a> {
+        VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::InputStepMode::Instance,
+            attributes: &[
+                // the position and the size
+                wgpu::VertexAttribute {
+                    shader_location: 1,
+                    offset: 0,
+                    format: VertexFormat::Float4,
+                },
+                wgpu::VertexAttribute {
+                    shader_location: 2,
+                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    format: VertexFormat::Float4,
+                },
+            ],
+        }
+    }
+    pub fn corner_desc<'a>() -> VertexBufferLayout<'a> {
+        VertexBufferLayout {
+            array_stride: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+            step_mode: wgpu::InputStepMode::Vertex,
+            attributes: &[wgpu::VertexAttribute {
+                shader_location: 0,
+                offset: 0,
+                format: VertexFormat::Float2,
+            }],
+        }
+    }
+}
@@ -0,0 +1,106 @@
+[[location(0)]] var<in> in_corner: vec2<f32>;
+[[location(1)]] var<in> in_position: vec4<f32>;
+[[location(2)]] var<in> in_color: vec4<f32>;
+
+[[builtin(position)]]
+var<out> out_position: vec4<f32>;
+
+[[location(0)]]
+var<out> out_color: vec4<f32>;
+
+[[block]]
+struct Globals {
+    view_proj: mat4x4<f32>;
+    camera_pos: vec4<f32>;
+    num_lights: vec4<u32>;
+    ambient_color: vec4<f32>;
+    enable_lighting: u32;
+    padding_0: u32;
+    padding_1: u32;
+    padding_2: u32;
+    camera_right: vec4<f32>;
+    camera_up: vec4<f32>;
+    viewport_size: vec4<f32>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> u_globals: Globals;
+
+[[block]]
+struct SimpleLight {
+    color: vec4<f32>;
+    pos: vec4<f32>;
+    constant: f32;
+    linear: f32;
+    quadratic: f32;
+};
+
+[[block]]
+struct SimpleLights {
+    data: [[stride(44)]] array<SimpleLight>;
+};
+
+[[group(0), binding(1)]]
+var<storage> simple_lights: [[access(read)]] SimpleLights;
+
+[[block]]
+struct PointCloud {
+    world_size: u32;
+    lit: u32;
+};
+
+[[group(1), binding(0)]]
+var<uniform> u_cloud: PointCloud;
+
+// the size of the point is in the w component of its position
+[[stage(vertex)]]
+fn vs_main() {
+    const size: f32 = in_position.w;
+    if (u_cloud.world_size != 0) {
+        const offset: vec3<f32> = (u_globals.camera_right.xyz * in_corner.x + u_globals.camera_up.xyz * in_corner.y) * size;
+        out_position = u_globals.view_proj * vec4<f32>(in_position.xyz + offset, 1.0);
+    } else {
+        const center: vec4<f32> = u_globals.view_proj * vec4<f32>(in_position.xyz, 1.0);
+        // a pixel is 2 / viewport_size wide in normalized device coordinates, multiplied by w to undo the perspective divide
+        const offset: vec2<f32> = in_corner * size * 2.0 * center.w / u_globals.viewport_size.xy;
+        out_position = vec4<f32>(center.xy + offset, center.zw);
+    }
+
+    // the points have no normal, they are lit as if they were facing every light
+    if (u_cloud.lit != 0 && u_globals.enable_lighting != 0) {
+        var color: vec4<f32> = u_globals.ambient_color;
+        var i: u32 = 0u;
+        loop {
+            if (i >= u_globals.num_lights.y) {
+                break;
+            }
+            const light: SimpleLight = simple_lights.data[i];
+
+            var attenuation: f32 = 1.0;
+            // directional lights have no position to be far away from
+            if (light.pos.w != 0.0) {
+                const d: f32 = distance(light.pos.xyz, in_position.xyz);
+                attenuation = 1.0 / (light.constant + light.linear * d + light.quadratic * (d * d));
+            }
+            color = color + vec4<f32>(light.color.xyz, 1.0) * light.color.w * attenuation;
+
+            continuing {
+                i = i + 1u;
+            }
+        }
+        out_color = vec4<f32>(color.xyz * in_color.xyz, in_color.w);
+    } else {
+        out_color = in_color;
+    }
+}
+
+[[location(0)]]
+var<in> v_color: vec4<f32>;
+
+[[location(0)]]
+var<out> out_color_fs: vec4<f32>;
+
+[[stage(fragment)]]
+fn fs_main() {
+    out_color_fs = v_color;
+}
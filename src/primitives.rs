@@ -0,0 +1,377 @@
+use crate::mesh_utils::compute_tangents_u32;
+use crate::{ColorVertex, Indices, ModelVertex, UvVertex};
+use cgmath::{InnerSpace, Vector2, Vector3, Vector4, Zero};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+/// the geometry of a generated shape, centered on the origin with y pointing up
+/// turn it into vertices for a ColorMesh, a UvMesh or a Model with the methods below
+/// the triangles are counter clockwise when seen from the outside
+#[derive(Debug, Clone, Default)]
+pub struct Primitive {
+    pub positions: Vec<Vector3<f32>>,
+    pub normals: Vec<Vector3<f32>>,
+    pub uvs: Vec<Vector2<f32>>,
+    pub indices: Vec<u32>,
+}
+
+/// a point of the outline that is rotated around the y axis by Primitive::push_revolution
+struct ProfilePoint {
+    radius: f32,
+    y: f32,
+    /// the normal in the plane of the outline, x points away from the axis
+    normal: Vector2<f32>,
+    v: f32,
+}
+
+impl Primitive {
+    /// a cube with the given edge length, every face is split into subdivisions * subdivisions quads
+    pub fn cube(size: f32, subdivisions: u32) -> Self {
+        let subdivisions = subdivisions.max(1);
+        let half = size / 2.0;
+        let faces = [
+            (Vector3::unit_x(), -Vector3::unit_z(), Vector3::unit_y()),
+            (-Vector3::unit_x(), Vector3::unit_z(), Vector3::unit_y()),
+            (Vector3::unit_y(), Vector3::unit_x(), -Vector3::unit_z()),
+            (-Vector3::unit_y(), Vector3::unit_x(), Vector3::unit_z()),
+            (Vector3::unit_z(), Vector3::unit_x(), Vector3::unit_y()),
+            (-Vector3::unit_z(), -Vector3::unit_x(), Vector3::unit_y()),
+        ];
+        let mut primitive = Self::default();
+        for &(normal, u, v) in faces.iter() {
+            primitive.push_grid(
+                (normal - u - v) * half,
+                u * size,
+                v * size,
+                normal,
+                (subdivisions, subdivisions),
+            );
+        }
+        primitive
+    }
+    /// a flat grid in the xz plane facing up
+    pub fn plane(width: f32, depth: f32, subdivisions_x: u32, subdivisions_z: u32) -> Self {
+        let mut primitive = Self::default();
+        primitive.push_grid(
+            Vector3::new(-width / 2.0, 0.0, depth / 2.0),
+            Vector3::unit_x() * width,
+            -Vector3::unit_z() * depth,
+            Vector3::unit_y(),
+            (subdivisions_x.max(1), subdivisions_z.max(1)),
+        );
+        primitive
+    }
+    /// a sphere made of segments around the y axis and rings from the top to the bottom
+    pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Self {
+        let rings = rings.max(2);
+        let profile = (0..=rings)
+            .map(|ring| {
+                let theta = PI * ring as f32 / rings as f32;
+                let normal = Vector2::new(theta.sin(), theta.cos());
+                ProfilePoint {
+                    radius: if ring == 0 || ring == rings {
+                        0.0
+                    } else {
+                        normal.x * radius
+                    },
+                    y: normal.y * radius,
+                    normal,
+                    v: ring as f32 / rings as f32,
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut primitive = Self::default();
+        primitive.push_revolution(&profile, segments);
+        primitive
+    }
+    /// a sphere made of evenly sized triangles
+    /// every subdivision splits each triangle of an icosahedron into four
+    pub fn icosphere(radius: f32, subdivisions: u32) -> Self {
+        let t = (1.0 + 5f32.sqrt()) / 2.0;
+        let mut positions = vec![
+            Vector3::new(-1.0, t, 0.0),
+            Vector3::new(1.0, t, 0.0),
+            Vector3::new(-1.0, -t, 0.0),
+            Vector3::new(1.0, -t, 0.0),
+            Vector3::new(0.0, -1.0, t),
+            Vector3::new(0.0, 1.0, t),
+            Vector3::new(0.0, -1.0, -t),
+            Vector3::new(0.0, 1.0, -t),
+            Vector3::new(t, 0.0, -1.0),
+            Vector3::new(t, 0.0, 1.0),
+            Vector3::new(-t, 0.0, -1.0),
+            Vector3::new(-t, 0.0, 1.0),
+        ]
+        .into_iter()
+        .map(|position| position.normalize())
+        .collect::<Vec<_>>();
+        let mut indices: Vec<u32> = vec![
+            0, 11, 5, 0, 5, 1, 0, 1, 7, 0, 7, 10, 0, 10, 11, 1, 5, 9, 5, 11, 4, 11, 10, 2, 10, 7,
+            6, 7, 1, 8, 3, 9, 4, 3, 4, 2, 3, 2, 6, 3, 6, 8, 3, 8, 9, 4, 9, 5, 2, 4, 11, 6, 2, 10,
+            8, 6, 7, 9, 8, 1,
+        ];
+        for _ in 0..subdivisions {
+            let mut midpoints = HashMap::new();
+            let mut midpoint = |a: u32, b: u32| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let position = (positions[a as usize] + positions[b as usize]).normalize();
+                    positions.push(position);
+                    positions.len() as u32 - 1
+                })
+            };
+            indices = indices
+                .chunks(3)
+                .flat_map(|triangle| {
+                    let (a, b, c) = (triangle[0], triangle[1], triangle[2]);
+                    let ab = midpoint(a, b);
+                    let bc = midpoint(b, c);
+                    let ca = midpoint(c, a);
+                    vec![a, ab, ca, b, bc, ab, c, ca, bc, ab, bc, ca]
+                })
+                .collect();
+        }
+        let mut uvs = positions
+            .iter()
+            .map(|position| {
+                Vector2::new(
+                    (-position.z).atan2(position.x).rem_euclid(2.0 * PI) / (2.0 * PI),
+                    position.y.max(-1.0).min(1.0).acos() / PI,
+                )
+            })
+            .collect::<Vec<_>>();
+        // triangles that cross the seam at u = 0 get a copy of their vertices with u moved past 1
+        let mut wrapped = HashMap::new();
+        for triangle in indices.chunks_mut(3) {
+            let max_u = triangle
+                .iter()
+                .map(|&index| uvs[index as usize].x)
+                .fold(0.0, f32::max);
+            for index in triangle.iter_mut() {
+                let original = *index;
+                if max_u - uvs[original as usize].x > 0.5 {
+                    *index = *wrapped.entry(original).or_insert_with(|| {
+                        positions.push(positions[original as usize]);
+                        uvs.push(uvs[original as usize] + Vector2::unit_x());
+                        positions.len() as u32 - 1
+                    });
+                }
+            }
+        }
+        Self {
+            normals: positions.clone(),
+            positions: positions.into_iter().map(|p| p * radius).collect(),
+            uvs,
+            indices,
+        }
+    }
+    /// a cylinder around the y axis with closed ends
+    pub fn cylinder(radius: f32, height: f32, segments: u32, height_segments: u32) -> Self {
+        let height_segments = height_segments.max(1);
+        let half = height / 2.0;
+        let side = (0..=height_segments)
+            .map(|row| {
+                let v = row as f32 / height_segments as f32;
+                ProfilePoint {
+                    radius,
+                    y: half - v * height,
+                    normal: Vector2::unit_x(),
+                    v,
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut primitive = Self::default();
+        primitive.push_cap(radius, half, Vector2::unit_y(), segments);
+        primitive.push_revolution(&side, segments);
+        primitive.push_cap(radius, -half, -Vector2::unit_y(), segments);
+        primitive
+    }
+    /// a cone around the y axis with its tip at the top and a closed base
+    pub fn cone(radius: f32, height: f32, segments: u32, height_segments: u32) -> Self {
+        let height_segments = height_segments.max(1);
+        let half = height / 2.0;
+        let normal = Vector2::new(height, radius).normalize();
+        let side = (0..=height_segments)
+            .map(|row| {
+                let v = row as f32 / height_segments as f32;
+                ProfilePoint {
+                    radius: v * radius,
+                    y: half - v * height,
+                    normal,
+                    v,
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut primitive = Self::default();
+        primitive.push_revolution(&side, segments);
+        primitive.push_cap(radius, -half, -Vector2::unit_y(), segments);
+        primitive
+    }
+    /// a ring in the xz plane
+    /// radius is the distance from the center to the middle of the tube, sides go around the tube
+    pub fn torus(radius: f32, tube_radius: f32, segments: u32, sides: u32) -> Self {
+        let sides = sides.max(3);
+        let profile = (0..=sides)
+            .map(|side| {
+                let v = side as f32 / sides as f32;
+                let angle = -2.0 * PI * v;
+                let normal = Vector2::new(angle.cos(), angle.sin());
+                ProfilePoint {
+                    radius: radius + normal.x * tube_radius,
+                    y: normal.y * tube_radius,
+                    normal,
+                    v,
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut primitive = Self::default();
+        primitive.push_revolution(&profile, segments);
+        primitive
+    }
+    /// a cylinder with half spheres at both ends
+    /// height is the length of the cylinder between the two half spheres, rings are per half sphere
+    pub fn capsule(radius: f32, height: f32, segments: u32, rings: u32) -> Self {
+        let rings = rings.max(1);
+        let half = height / 2.0;
+        let length = PI * radius + height;
+        let mut profile = vec![];
+        for (offset, first_ring) in [(half, 0), (-half, rings)].iter() {
+            for ring in *first_ring..=*first_ring + rings {
+                let theta = PI * ring as f32 / (2 * rings) as f32;
+                let normal = Vector2::new(theta.sin(), theta.cos());
+                let arc = theta * radius + if *offset < 0.0 { height } else { 0.0 };
+                profile.push(ProfilePoint {
+                    radius: if ring == 0 || ring == 2 * rings {
+                        0.0
+                    } else {
+                        normal.x * radius
+                    },
+                    y: normal.y * radius + offset,
+                    normal,
+                    v: arc / length,
+                });
+            }
+        }
+        let mut primitive = Self::default();
+        primitive.push_revolution(&profile, segments);
+        primitive
+    }
+    /// vertices for a ColorMesh, every vertex gets the same color
+    pub fn color_vertices(&self, color: Vector4<f32>) -> Vec<ColorVertex> {
+        self.positions
+            .iter()
+            .zip(self.normals.iter())
+            .map(|(position, normal)| ColorVertex::new(*position, *normal, color))
+            .collect()
+    }
+    /// vertices for a UvMesh
+    pub fn uv_vertices(&self) -> Vec<UvVertex> {
+        self.positions
+            .iter()
+            .zip(self.normals.iter())
+            .zip(self.uvs.iter())
+            .map(|((position, normal), uv)| UvVertex::new(*position, *normal, *uv))
+            .collect()
+    }
+    /// vertices for a Model, the tangents follow the direction of the uvs
+    pub fn model_vertices(&self) -> Vec<ModelVertex> {
+        let mut vertices = self
+            .positions
+            .iter()
+            .zip(self.normals.iter())
+            .zip(self.uvs.iter())
+            .map(|((position, normal), uv)| {
+                ModelVertex::new(*position, *normal, *uv, Vector3::zero(), Vector3::zero())
+            })
+            .collect::<Vec<_>>();
+        compute_tangents_u32(&mut vertices, &self.indices);
+        vertices
+    }
+    /// the indices, 16 bit if there are few enough vertices
+    pub fn indices(&self) -> Indices {
+        Indices::smallest(self.indices.clone(), self.positions.len())
+    }
+    fn push_vertex(&mut self, position: Vector3<f32>, normal: Vector3<f32>, uv: Vector2<f32>) {
+        self.positions.push(position);
+        self.normals.push(normal);
+        self.uvs.push(uv);
+    }
+    /// a flat grid of quads, u and v span the whole grid and normal has to be u cross v
+    fn push_grid(
+        &mut self,
+        origin: Vector3<f32>,
+        u: Vector3<f32>,
+        v: Vector3<f32>,
+        normal: Vector3<f32>,
+        subdivisions: (u32, u32),
+    ) {
+        let first = self.positions.len() as u32;
+        let (columns, rows) = subdivisions;
+        for row in 0..=rows {
+            for column in 0..=columns {
+                let s = column as f32 / columns as f32;
+                let t = row as f32 / rows as f32;
+                self.push_vertex(origin + u * s + v * t, normal, Vector2::new(s, 1.0 - t));
+            }
+        }
+        for row in 0..rows {
+            for column in 0..columns {
+                let a = first + row * (columns + 1) + column;
+                let b = a + 1;
+                let c = b + columns + 1;
+                let d = a + columns + 1;
+                self.indices.extend_from_slice(&[a, b, c, a, c, d]);
+            }
+        }
+    }
+    /// rotate the profile around the y axis
+    /// the profile has to go from the top to the bottom, points with a radius of 0 are poles
+    fn push_revolution(&mut self, profile: &[ProfilePoint], segments: u32) {
+        let segments = segments.max(3);
+        let first = self.positions.len() as u32;
+        for point in profile {
+            for segment in 0..=segments {
+                let u = segment as f32 / segments as f32;
+                let (sin, cos) = (2.0 * PI * u).sin_cos();
+                self.push_vertex(
+                    Vector3::new(cos * point.radius, point.y, -sin * point.radius),
+                    Vector3::new(cos * point.normal.x, point.normal.y, -sin * point.normal.x),
+                    Vector2::new(u, point.v),
+                );
+            }
+        }
+        for (row, pair) in profile.windows(2).enumerate() {
+            for segment in 0..segments {
+                let a = first + row as u32 * (segments + 1) + segment;
+                let b = a + segments + 1;
+                let c = b + 1;
+                let d = a + 1;
+                if pair[1].radius != 0.0 {
+                    self.indices.extend_from_slice(&[a, b, c]);
+                }
+                if pair[0].radius != 0.0 {
+                    self.indices.extend_from_slice(&[a, c, d]);
+                }
+            }
+        }
+    }
+    /// a flat disk at the height y facing up or down
+    fn push_cap(&mut self, radius: f32, y: f32, normal: Vector2<f32>, segments: u32) {
+        let center = ProfilePoint {
+            radius: 0.0,
+            y,
+            normal,
+            v: 0.0,
+        };
+        let rim = ProfilePoint {
+            radius,
+            y,
+            normal,
+            v: 1.0,
+        };
+        if normal.y > 0.0 {
+            self.push_revolution(&[center, rim], segments);
+        } else {
+            self.push_revolution(&[rim, center], segments);
+        }
+    }
+}
@@ -1,17 +1,520 @@
 use crate::api::lights::RealLightRaw;
-use crate::WgpuRenderer;
+use crate::bounds::{visible_instances, Frustum};
+use crate::color_mesh::ColorMesh;
+use crate::render_target::{Frame, RenderTarget};
+use crate::shapes::tessellate_shapes;
+use crate::slots::SlotKey;
+use crate::sprites::Sprite;
+use crate::transparency::{push_transparent_instances, sort_back_to_front, TransparentInstance};
+use crate::uniforms::GlobalUniforms;
+use crate::uv_mesh::UvModel;
+use crate::viewport::PixelRect;
+use crate::window::ExtraWindow;
+use crate::{Background, BlendMode, RendererError, WgpuRenderer, DEFAULT_LAYER, QUAD_CORNERS};
+use cgmath::EuclideanSpace;
 use futures::task::SpawnExt;
 use std::mem;
+use wgpu::util::DeviceExt;
 use wgpu_glyph::{BuiltInLineBreaker, Layout, Section, Text};
 
 pub trait Render {
-    fn render(&mut self);
+    fn render(&mut self) -> Result<(), RendererError>;
 }
 
 impl Render for WgpuRenderer {
-    fn render(&mut self) {
-        let frame = self.swap_chain.get_current_frame().unwrap();
+    /// draw a frame and present it
+    /// frames are skipped while the window has no area or the swap chain can't provide a frame in time
+    /// a lost or outdated swap chain is recreated, only running out of memory is reported as an error
+    fn render(&mut self) -> Result<(), RendererError> {
+        if self.sc_desc.width == 0 || self.sc_desc.height == 0 {
+            return Ok(());
+        }
+
+        let frame = match acquire_frame(&mut self.target, &self.device, &self.sc_desc)? {
+            Some(frame) => frame,
+            None => return Ok(()),
+        };
+
+        if self.capture_requested {
+            self.render_and_capture(frame.view())?;
+        } else {
+            self.render_to_views(&[frame.view()]);
+        }
+        self.frame_rendered = true;
+        Ok(())
+    }
+}
+
+/// get the next frame of a render target
+/// a lost or outdated swap chain is recreated and asked again
+/// None means this frame should be skipped, only running out of memory is an error
+fn acquire_frame(
+    target: &mut RenderTarget,
+    device: &wgpu::Device,
+    sc_desc: &wgpu::SwapChainDescriptor,
+) -> Result<Option<Frame>, RendererError> {
+    let frame = match target.next_frame() {
+        Err(wgpu::SwapChainError::Outdated) | Err(wgpu::SwapChainError::Lost) => {
+            target.rebuild(device, sc_desc);
+            target.next_frame()
+        }
+        frame => frame,
+    };
+    match frame {
+        Ok(frame) => Ok(Some(frame)),
+        Err(wgpu::SwapChainError::OutOfMemory) => Err(wgpu::SwapChainError::OutOfMemory.into()),
+        // the swap chain was just recreated or the gpu is busy, try again next frame
+        Err(_) => Ok(None),
+    }
+}
+
+/// where a forward pass draws to and what it draws
+struct ForwardTarget<'a> {
+    color: &'a wgpu::TextureView,
+    resolve_target: Option<&'a wgpu::TextureView>,
+    depth: &'a wgpu::TextureView,
+    /// clear the color target with the background, otherwise draw on top of it
+    clear: bool,
+    /// the index of the GlobalUniforms in the view uniforms buffer
+    uniforms: usize,
+    rect: PixelRect,
+    layer_mask: u32,
+    /// the render texture that is drawn into, meshes showing it are skipped
+    render_texture: Option<SlotKey>,
+}
+
+/// a mesh with a blend mode that is not opaque
+#[derive(Copy, Clone)]
+enum TransparentMesh<'a> {
+    Color(&'a ColorMesh),
+    Uv(&'a UvModel),
+    Sprite(&'a Sprite),
+}
+
+impl TransparentMesh<'_> {
+    fn is(&self, other: &Self) -> bool {
+        match (self, other) {
+            (TransparentMesh::Color(a), TransparentMesh::Color(b)) => std::ptr::eq(*a, *b),
+            (TransparentMesh::Uv(a), TransparentMesh::Uv(b)) => std::ptr::eq(*a, *b),
+            (TransparentMesh::Sprite(a), TransparentMesh::Sprite(b)) => std::ptr::eq(*a, *b),
+            _ => false,
+        }
+    }
+}
+
+impl WgpuRenderer {
+    /// the index of the uniforms, the area in pixels and the layer mask of every view that covers part of the screen
+    /// the main view comes first, then the viewports in the order they were added
+    fn visible_views(&self) -> Vec<(usize, PixelRect, u32)> {
+        let size = (self.sc_desc.width, self.sc_desc.height);
+        let main = (self.main_viewport_rect, self.main_layer_mask);
+        std::iter::once(main)
+            .chain(
+                self.viewport_order
+                    .iter()
+                    .filter_map(|&key| self.viewports.get(key))
+                    .map(|viewport| (viewport.rect, viewport.layer_mask)),
+            )
+            .enumerate()
+            .filter_map(|(index, (rect, layer_mask))| {
+                PixelRect::from_normalized(rect, size).map(|rect| (index, rect, layer_mask))
+            })
+            .collect()
+    }
+    /// draw everything in the layers of the target into it with a single render pass
+    fn forward_pass(&self, encoder: &mut wgpu::CommandEncoder, target: ForwardTarget<'_>) {
+        // the passes only have room for the uniforms of one view
+        // views added since the last update keep the uniforms of the previous view for one frame
+        let uniform_size = mem::size_of::<GlobalUniforms>() as wgpu::BufferAddress;
+        let uniform_bufs = if target.uniforms < self.views_in_buffer {
+            &[
+                &self.passes.color_forward_pass.uniform_buf,
+                &self.passes.uv_forward_pass.uniform_buf,
+                &self.passes.model_forward_pass.uniform_buf,
+                &self.passes.line_forward_pass.uniform_buf,
+                &self.passes.point_cloud_forward_pass.uniform_buf,
+            ][..]
+        } else {
+            &[]
+        };
+        for uniform_buf in uniform_bufs {
+            encoder.copy_buffer_to_buffer(
+                &self.view_uniforms_buf,
+                target.uniforms as wgpu::BufferAddress * uniform_size,
+                uniform_buf,
+                0,
+                uniform_size,
+            );
+        }
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: target.color,
+                resolve_target: target.resolve_target,
+                ops: wgpu::Operations {
+                    load: if target.clear {
+                        self.background.load_op()
+                    } else {
+                        wgpu::LoadOp::Load
+                    },
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                attachment: target.depth,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: false,
+                }),
+                stencil_ops: None,
+            }),
+        });
+        if let (true, Background::VerticalGradient { .. }) = (target.clear, self.background) {
+            pass.set_pipeline(&self.passes.background_pipeline);
+            pass.set_vertex_buffer(0, self.background_buf.slice(..));
+            pass.draw(0..4, 0..1);
+        }
+        let rect = target.rect;
+        pass.set_viewport(
+            rect.x as f32,
+            rect.y as f32,
+            rect.width as f32,
+            rect.height as f32,
+            0.0,
+            1.0,
+        );
+        pass.set_scissor_rect(rect.x, rect.y, rect.width, rect.height);
+        let layer_mask = target.layer_mask;
+        // views added since the last update have no frustum yet and are drawn without culling
+        let frustum = self.view_frustums.get(target.uniforms);
+        let passes = &self.passes;
+        if !self.color_meshes.is_empty() {
+            pass.set_bind_group(0, &passes.color_forward_pass.bind_group, &[]); // the globals and simple lights
+
+            for color_mesh in self.color_meshes.iter() {
+                if !color_mesh.is_empty()
+                    && color_mesh.layers & layer_mask != 0
+                    && !color_mesh.blend_mode.is_transparent()
+                {
+                    let key = passes.color_pipelines.key(
+                        color_mesh.render_state,
+                        BlendMode::Opaque,
+                        color_mesh.indices_format(),
+                    );
+                    let pipeline = match passes
+                        .color_pipelines
+                        .pipeline(&passes.color_forward_pass.pipeline, key)
+                    {
+                        Some(pipeline) => pipeline,
+                        None => continue,
+                    };
+                    pass.set_pipeline(pipeline);
+                    if let Some(index_buf) = &color_mesh.index_buf {
+                        pass.set_index_buffer(index_buf.slice(..), color_mesh.index_format);
+                    }
+                    pass.set_vertex_buffer(0, color_mesh.vertex_buf.slice(..));
+                    pass.set_vertex_buffer(1, color_mesh.instance_buffer.slice(..));
 
+                    let draw_range = color_mesh.draw_range();
+                    for instances in visible_instances(
+                        &color_mesh.instance_bounds,
+                        color_mesh.instances_in_buffer,
+                        frustum,
+                    ) {
+                        draw_range.draw(&mut pass, instances);
+                    }
+                }
+            }
+        }
+        if !self.uv_meshes.is_empty() {
+            pass.set_bind_group(0, &passes.uv_forward_pass.bind_group, &[]); // the globals and simple lights
+            for uv_mesh in self.uv_meshes.iter() {
+                // a mesh that shows this render texture can't be drawn into it
+                let samples_target = target.render_texture.is_some()
+                    && uv_mesh.render_texture == target.render_texture;
+                if !uv_mesh.is_empty()
+                    && uv_mesh.layers & layer_mask != 0
+                    && !samples_target
+                    && !uv_mesh.blend_mode.is_transparent()
+                {
+                    let key = passes.uv_pipelines.key(
+                        uv_mesh.render_state,
+                        BlendMode::Opaque,
+                        uv_mesh.indices_format(),
+                    );
+                    let pipeline = match passes
+                        .uv_pipelines
+                        .pipeline(&passes.uv_forward_pass.pipeline, key)
+                    {
+                        Some(pipeline) => pipeline,
+                        None => continue,
+                    };
+                    pass.set_pipeline(pipeline);
+                    pass.set_bind_group(1, &uv_mesh.diffuse_bind_group, &[]);
+                    pass.set_vertex_buffer(0, uv_mesh.vertex_buffer.slice(..));
+                    pass.set_vertex_buffer(1, uv_mesh.instance_buffer.slice(..));
+                    if let Some(index_buffer) = &uv_mesh.index_buffer {
+                        pass.set_index_buffer(index_buffer.slice(..), uv_mesh.index_format);
+                    }
+                    let draw_range = uv_mesh.draw_range();
+                    for instances in visible_instances(
+                        &uv_mesh.instance_bounds,
+                        uv_mesh.instances_in_buffer,
+                        frustum,
+                    ) {
+                        draw_range.draw(&mut pass, instances);
+                    }
+                }
+            }
+        }
+        // the pipeline of opaque sprites is created together with the pass
+        let opaque_sprites =
+            passes
+                .sprite_pipelines
+                .key(Sprite::render_state(), BlendMode::Opaque, None);
+        if let (false, Some(pipeline)) = (
+            self.sprites.is_empty(),
+            passes.sprite_pipelines.get(opaque_sprites),
+        ) {
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &passes.uv_forward_pass.bind_group, &[]); // the globals and lights of the uv meshes
+            pass.set_vertex_buffer(0, self.quad_corners_buf.slice(..));
+            for sprite in self.sprites.iter() {
+                if !sprite.is_empty()
+                    && sprite.layers & layer_mask != 0
+                    && !sprite.blend_mode.is_transparent()
+                {
+                    pass.set_bind_group(1, &sprite.bind_group, &[]);
+                    pass.set_vertex_buffer(1, sprite.instance_buffer.slice(..));
+                    for instances in visible_instances(
+                        &sprite.instance_bounds,
+                        sprite.instances_in_buffer,
+                        frustum,
+                    ) {
+                        pass.draw(0..QUAD_CORNERS.len() as u32, instances);
+                    }
+                }
+            }
+        }
+        if !self.models.is_empty() {
+            pass.set_bind_group(0, &passes.model_forward_pass.bind_group, &[]); // the globals
+            for model in self.models.iter() {
+                if !model.is_empty() && model.layers & layer_mask != 0 {
+                    let key = passes.model_pipelines.key(
+                        model.render_state,
+                        BlendMode::Opaque,
+                        Some(wgpu::IndexFormat::Uint32),
+                    );
+                    let pipeline = match passes
+                        .model_pipelines
+                        .pipeline(&passes.model_forward_pass.pipeline, key)
+                    {
+                        Some(pipeline) => pipeline,
+                        None => continue,
+                    };
+                    pass.set_pipeline(pipeline);
+                    for mesh in &model.meshes {
+                        pass.set_bind_group(1, &model.materials[mesh.material].bind_group, &[]);
+                        pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                        pass.set_vertex_buffer(1, model.instance_buffer.slice(..));
+                        pass.set_index_buffer(
+                            mesh.index_buffer.slice(..),
+                            wgpu::IndexFormat::Uint32,
+                        );
+                        for instances in visible_instances(
+                            &mesh.instance_bounds,
+                            model.instances_in_buffer(),
+                            frustum,
+                        ) {
+                            pass.draw_indexed(0..mesh.indices.len() as u32, 0, instances);
+                        }
+                    }
+                }
+            }
+        }
+        // lines are always in the default layer
+        if !self.lines.is_empty() && layer_mask & DEFAULT_LAYER != 0 {
+            pass.set_pipeline(&self.passes.line_forward_pass.pipeline);
+            pass.set_bind_group(0, &self.passes.line_forward_pass.bind_group, &[]);
+            pass.set_vertex_buffer(0, self.lines.vertex_buffer.slice(..));
+            pass.draw(0..self.lines.number_of_vertices() as u32, 0..1);
+        }
+        if !self.point_clouds.is_empty() {
+            pass.set_pipeline(&passes.point_cloud_forward_pass.pipeline);
+            pass.set_bind_group(0, &passes.point_cloud_forward_pass.bind_group, &[]); // the globals and simple lights
+            pass.set_vertex_buffer(0, self.quad_corners_buf.slice(..));
+            for cloud in self.point_clouds.iter() {
+                if !cloud.is_empty() && cloud.layers & layer_mask != 0 && cloud.is_visible(frustum)
+                {
+                    pass.set_bind_group(1, &cloud.bind_group, &[]);
+                    pass.set_vertex_buffer(1, cloud.point_buf.slice(..));
+                    pass.draw(
+                        0..QUAD_CORNERS.len() as u32,
+                        0..cloud.points_in_buffer as u32,
+                    );
+                }
+            }
+        }
+        self.transparent_draws(&mut pass, &target, frustum);
+    }
+    /// draw the visible instances of the transparent meshes in the layers of the target from back to front
+    /// they come after everything opaque, so they are blended with it and hidden behind it
+    fn transparent_draws<'a>(
+        &'a self,
+        pass: &mut wgpu::RenderPass<'a>,
+        target: &ForwardTarget<'_>,
+        frustum: Option<&Frustum>,
+    ) {
+        // views added since the last update are sorted by the distance to the main camera for one frame
+        let eye = self
+            .view_positions
+            .get(target.uniforms)
+            .copied()
+            .unwrap_or_else(|| self.camera.get_position())
+            .to_vec();
+        let mut instances: Vec<TransparentInstance<TransparentMesh<'a>>> = vec![];
+        // every pipeline is created when a mesh is loaded or changed, a mesh without one is skipped
+        for color_mesh in self.color_meshes.iter() {
+            let key = self.passes.color_pipelines.key(
+                color_mesh.render_state,
+                color_mesh.blend_mode,
+                color_mesh.indices_format(),
+            );
+            if color_mesh.blend_mode.is_transparent()
+                && !color_mesh.is_empty()
+                && color_mesh.layers & target.layer_mask != 0
+                && self.passes.color_pipelines.get(key).is_some()
+            {
+                push_transparent_instances(
+                    &mut instances,
+                    TransparentMesh::Color(color_mesh),
+                    &color_mesh.instance_bounds,
+                    color_mesh
+                        .instances
+                        .iter()
+                        .map(|instance| instance.transform.position),
+                    color_mesh.instances_in_buffer,
+                    frustum,
+                    eye,
+                );
+            }
+        }
+        for uv_mesh in self.uv_meshes.iter() {
+            let samples_target =
+                target.render_texture.is_some() && uv_mesh.render_texture == target.render_texture;
+            let key = self.passes.uv_pipelines.key(
+                uv_mesh.render_state,
+                uv_mesh.blend_mode,
+                uv_mesh.indices_format(),
+            );
+            if uv_mesh.blend_mode.is_transparent()
+                && !uv_mesh.is_empty()
+                && uv_mesh.layers & target.layer_mask != 0
+                && !samples_target
+                && self.passes.uv_pipelines.get(key).is_some()
+            {
+                push_transparent_instances(
+                    &mut instances,
+                    TransparentMesh::Uv(uv_mesh),
+                    &uv_mesh.instance_bounds,
+                    uv_mesh.instances.iter().map(|transform| transform.position),
+                    uv_mesh.instances_in_buffer,
+                    frustum,
+                    eye,
+                );
+            }
+        }
+        for sprite in self.sprites.iter() {
+            let key =
+                self.passes
+                    .sprite_pipelines
+                    .key(Sprite::render_state(), sprite.blend_mode, None);
+            if sprite.blend_mode.is_transparent()
+                && !sprite.is_empty()
+                && sprite.layers & target.layer_mask != 0
+                && self.passes.sprite_pipelines.get(key).is_some()
+            {
+                push_transparent_instances(
+                    &mut instances,
+                    TransparentMesh::Sprite(sprite),
+                    &sprite.instance_bounds,
+                    sprite.instances.iter().map(|instance| instance.position),
+                    sprite.instances_in_buffer,
+                    frustum,
+                    eye,
+                );
+            }
+        }
+        sort_back_to_front(&mut instances);
+
+        // every instance is drawn on its own, the buffers are only bound again when the mesh changes
+        let mut bound: Option<TransparentMesh<'a>> = None;
+        for TransparentInstance { mesh, instance, .. } in instances {
+            if !bound.map_or(false, |bound| bound.is(&mesh)) {
+                match mesh {
+                    TransparentMesh::Color(color_mesh) => {
+                        let passes = &self.passes;
+                        let key = passes.color_pipelines.key(
+                            color_mesh.render_state,
+                            color_mesh.blend_mode,
+                            color_mesh.indices_format(),
+                        );
+                        if let Some(pipeline) = passes.color_pipelines.get(key) {
+                            pass.set_pipeline(pipeline);
+                        }
+                        pass.set_bind_group(0, &passes.color_forward_pass.bind_group, &[]);
+                        pass.set_vertex_buffer(0, color_mesh.vertex_buf.slice(..));
+                        pass.set_vertex_buffer(1, color_mesh.instance_buffer.slice(..));
+                        if let Some(index_buf) = &color_mesh.index_buf {
+                            pass.set_index_buffer(index_buf.slice(..), color_mesh.index_format);
+                        }
+                    }
+                    TransparentMesh::Uv(uv_mesh) => {
+                        let passes = &self.passes;
+                        let key = passes.uv_pipelines.key(
+                            uv_mesh.render_state,
+                            uv_mesh.blend_mode,
+                            uv_mesh.indices_format(),
+                        );
+                        if let Some(pipeline) = passes.uv_pipelines.get(key) {
+                            pass.set_pipeline(pipeline);
+                        }
+                        pass.set_bind_group(0, &passes.uv_forward_pass.bind_group, &[]);
+                        pass.set_bind_group(1, &uv_mesh.diffuse_bind_group, &[]);
+                        pass.set_vertex_buffer(0, uv_mesh.vertex_buffer.slice(..));
+                        pass.set_vertex_buffer(1, uv_mesh.instance_buffer.slice(..));
+                        if let Some(index_buffer) = &uv_mesh.index_buffer {
+                            pass.set_index_buffer(index_buffer.slice(..), uv_mesh.index_format);
+                        }
+                    }
+                    TransparentMesh::Sprite(sprite) => {
+                        let passes = &self.passes;
+                        let key = passes.sprite_pipelines.key(
+                            Sprite::render_state(),
+                            sprite.blend_mode,
+                            None,
+                        );
+                        if let Some(pipeline) = passes.sprite_pipelines.get(key) {
+                            pass.set_pipeline(pipeline);
+                        }
+                        pass.set_bind_group(0, &passes.uv_forward_pass.bind_group, &[]);
+                        pass.set_bind_group(1, &sprite.bind_group, &[]);
+                        pass.set_vertex_buffer(0, self.quad_corners_buf.slice(..));
+                        pass.set_vertex_buffer(1, sprite.instance_buffer.slice(..));
+                    }
+                }
+                bound = Some(mesh);
+            }
+            let instances = instance..instance + 1;
+            match mesh {
+                TransparentMesh::Color(color_mesh) => color_mesh.draw_range().draw(pass, instances),
+                TransparentMesh::Uv(uv_mesh) => uv_mesh.draw_range().draw(pass, instances),
+                TransparentMesh::Sprite(_) => pass.draw(0..QUAD_CORNERS.len() as u32, instances),
+            }
+        }
+    }
+    /// write the lights into their storage buffer if they were changed
+    fn write_lights(&mut self) {
         if self.lights_are_dirty {
             self.lights_are_dirty = false;
             for (i, light) in self.real_lights.iter().enumerate() {
@@ -22,15 +525,16 @@ impl Render for WgpuRenderer {
                 );
             }
         }
-
-        let mut encoder = self
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-
+    }
+    /// render the depth of everything into the shadow textures of the active lights
+    fn shadow_passes(&self, encoder: &mut wgpu::CommandEncoder) {
         if self.global_uniforms.lighting_enabled != 0 {
             encoder.push_debug_group("shadow passes");
             for (i, light) in self.real_lights.iter().enumerate() {
                 if light.active {
+                    // only what the light sees can cast a shadow into its texture
+                    let frustum = Frustum::from_camera(&light.camera);
+                    let frustum = Some(&frustum);
                     if !self.color_meshes.is_empty() {
                         // copy the view_proj_matrix from the current light into the global_uniforms of the shadow pass
                         encoder.copy_buffer_to_buffer(
@@ -93,8 +597,8 @@ impl Render for WgpuRenderer {
                             pass.set_pipeline(&self.passes.model_shadow_pass.pipeline);
                             pass.set_bind_group(0, &self.passes.model_shadow_pass.bind_group, &[]); // the globals
 
-                            for model in self.models.iter().flatten() {
-                                if !model.is_empty() {
+                            for model in self.models.iter() {
+                                if !model.is_empty() && model.render_state.casts_shadows() {
                                     for mesh in &model.meshes {
                                         pass.set_bind_group(
                                             1,
@@ -107,11 +611,17 @@ impl Render for WgpuRenderer {
                                             mesh.index_buffer.slice(..),
                                             wgpu::IndexFormat::Uint32,
                                         );
-                                        pass.draw_indexed(
-                                            0..mesh.indices.len() as u32,
-                                            0,
-                                            0..model.instances_in_buffer() as u32,
-                                        );
+                                        for instances in visible_instances(
+                                            &mesh.instance_bounds,
+                                            model.instances_in_buffer(),
+                                            frustum,
+                                        ) {
+                                            pass.draw_indexed(
+                                                0..mesh.indices.len() as u32,
+                                                0,
+                                                instances,
+                                            );
+                                        }
                                     }
                                 }
                             }
@@ -119,26 +629,25 @@ impl Render for WgpuRenderer {
                         if !self.color_meshes.is_empty() {
                             pass.set_pipeline(&self.passes.color_shadow_pass.pipeline);
                             pass.set_bind_group(0, &self.passes.color_shadow_pass.bind_group, &[]); // the globals
-                            for model in self.color_meshes.iter().flatten() {
-                                if !model.is_empty() {
+                            for model in self.color_meshes.iter() {
+                                if !model.is_empty() && model.render_state.casts_shadows() {
                                     pass.set_vertex_buffer(0, model.vertex_buf.slice(..));
                                     pass.set_vertex_buffer(1, model.instance_buffer.slice(..));
+                                    let visible = visible_instances(
+                                        &model.instance_bounds,
+                                        model.instances_in_buffer,
+                                        frustum,
+                                    );
                                     if let Some(index_buf) = &model.index_buf {
                                         pass.set_index_buffer(
                                             index_buf.slice(..),
-                                            wgpu::IndexFormat::Uint16,
-                                        );
-                                        pass.draw_indexed(
-                                            0..model.index_count as u32,
-                                            0,
-                                            0..model.instances.len() as u32,
-                                        );
-                                    } else {
-                                        pass.draw(
-                                            0..model.index_count as u32,
-                                            0..model.instances.len() as u32,
+                                            model.index_format,
                                         );
                                     }
+                                    let draw_range = model.draw_range();
+                                    for instances in visible {
+                                        draw_range.draw(&mut pass, instances);
+                                    }
                                 }
                             }
                         }
@@ -146,27 +655,26 @@ impl Render for WgpuRenderer {
                             pass.set_pipeline(&self.passes.uv_shadow_pass.pipeline);
                             pass.set_bind_group(0, &self.passes.uv_shadow_pass.bind_group, &[]); // the globals
 
-                            for uv_mesh in self.uv_meshes.iter().flatten() {
-                                if !uv_mesh.is_empty() {
+                            for uv_mesh in self.uv_meshes.iter() {
+                                if !uv_mesh.is_empty() && uv_mesh.render_state.casts_shadows() {
                                     pass.set_bind_group(1, &uv_mesh.diffuse_bind_group, &[]);
                                     pass.set_vertex_buffer(0, uv_mesh.vertex_buffer.slice(..));
                                     pass.set_vertex_buffer(1, uv_mesh.instance_buffer.slice(..));
+                                    let visible = visible_instances(
+                                        &uv_mesh.instance_bounds,
+                                        uv_mesh.instances_in_buffer,
+                                        frustum,
+                                    );
                                     if let Some(index_buffer) = &uv_mesh.index_buffer {
                                         pass.set_index_buffer(
                                             index_buffer.slice(..),
-                                            wgpu::IndexFormat::Uint16,
-                                        );
-                                        pass.draw_indexed(
-                                            0..uv_mesh.index_count as u32,
-                                            0,
-                                            0..uv_mesh.instances.len() as u32,
-                                        );
-                                    } else {
-                                        pass.draw(
-                                            0..uv_mesh.index_count as u32,
-                                            0..uv_mesh.index_count as u32,
+                                            uv_mesh.index_format,
                                         );
                                     }
+                                    let draw_range = uv_mesh.draw_range();
+                                    for instances in visible {
+                                        draw_range.draw(&mut pass, instances);
+                                    }
                                 }
                             }
                         }
@@ -182,162 +690,276 @@ impl Render for WgpuRenderer {
             }
             encoder.pop_debug_group();
         }
+    }
+    /// draw the shapes of the main window or of an extra window on top of the given view
+    /// the shapes are tessellated again every frame, they are cheap compared to the scene
+    fn shape_pass(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        size: (u32, u32),
+        window: Option<SlotKey>,
+    ) {
+        let shapes = match window {
+            Some(key) => match self.windows.get(key) {
+                Some(window) => &window.shapes,
+                None => return,
+            },
+            None => &self.shapes,
+        };
+        let vertices = tessellate_shapes(shapes, size);
+        if vertices.is_empty() {
+            return;
+        }
+        let vertex_buf = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("shape vertex buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsage::VERTEX,
+            });
 
-        // forward pass
-        encoder.push_debug_group("forward rendering pass");
+        encoder.push_debug_group("shape rendering");
         {
             let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: None,
                 color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
-                    attachment: &frame.output.view, // no we write to the screen
+                    attachment: view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.0,
-                            g: 0.0,
-                            b: 0.0,
-                            a: 1.0,
-                        }),
+                        load: wgpu::LoadOp::Load,
                         store: true,
                     },
                 }],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachmentDescriptor {
-                    attachment: &self.forward_depth, // just a normal depth buffer
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
-                        store: false,
-                    }),
-                    stencil_ops: None,
-                }),
+                depth_stencil_attachment: None,
             });
-            if !self.color_meshes.is_empty() {
-                pass.set_pipeline(&self.passes.color_forward_pass.pipeline);
-                pass.set_bind_group(0, &self.passes.color_forward_pass.bind_group, &[]); // the globals and simple lights
+            pass.set_pipeline(&self.passes.shape_pipeline);
+            pass.set_vertex_buffer(0, vertex_buf.slice(..));
+            pass.draw(0..vertices.len() as u32, 0..1);
+        }
+        encoder.pop_debug_group();
+    }
+    /// draw the paragraphs of the main window or of an extra window on top of the given view
+    fn text_pass(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        size: (u32, u32),
+        window: Option<SlotKey>,
+    ) {
+        let paragraphs = match window {
+            Some(key) => match self.windows.get(key) {
+                Some(window) => &window.paragraphs,
+                None => return,
+            },
+            None => &self.paragraphs,
+        };
+        if paragraphs.is_empty() {
+            return;
+        }
+        encoder.push_debug_group("text rendering");
+        for paragraph in paragraphs {
+            self.glyph_brush.queue(Section {
+                screen_position: (paragraph.position.x, paragraph.position.y),
+                bounds: (size.0 as f32, size.1 as f32),
+                layout: Layout::Wrap {
+                    line_breaker: BuiltInLineBreaker::AnyCharLineBreaker,
+                    h_align: paragraph.horizontal_alignment,
+                    v_align: paragraph.vertical_alignment,
+                },
+                text: paragraph
+                    .sections
+                    .iter()
+                    .map(|section| {
+                        Text::new(&section.text)
+                            .with_color(section.color)
+                            .with_scale(section.scale)
+                            .with_font_id(section.font)
+                    })
+                    .collect(),
+            });
+        }
 
-                for color_mesh in self.color_meshes.iter().flatten() {
-                    if !color_mesh.is_empty() {
-                        if let Some(index_buf) = &color_mesh.index_buf {
-                            pass.set_index_buffer(index_buf.slice(..), wgpu::IndexFormat::Uint16);
-                        }
-                        pass.set_vertex_buffer(0, color_mesh.vertex_buf.slice(..));
-                        pass.set_vertex_buffer(1, color_mesh.instance_buffer.slice(..));
+        // Draw the text!
+        self.glyph_brush
+            .draw_queued(
+                &self.device,
+                &mut self.staging_belt,
+                encoder,
+                view,
+                size.0,
+                size.1,
+            )
+            .expect("Draw queued");
 
-                        pass.draw(
-                            0..color_mesh.vertex_count as u32,
-                            0..color_mesh.instances.len() as u32,
-                        );
-                        pass.draw_indexed(
-                            0..color_mesh.index_count as u32,
-                            0,
-                            0..color_mesh.instances.len() as u32,
-                        );
-                    }
-                }
-            }
-            if !self.uv_meshes.is_empty() {
-                pass.set_pipeline(&self.passes.uv_forward_pass.pipeline);
-                pass.set_bind_group(0, &self.passes.uv_forward_pass.bind_group, &[]); // the globals and simple lights
-                for uv_mesh in self.uv_meshes.iter().flatten() {
-                    if !uv_mesh.is_empty() {
-                        pass.set_bind_group(1, &uv_mesh.diffuse_bind_group, &[]);
-                        pass.set_vertex_buffer(0, uv_mesh.vertex_buffer.slice(..));
-                        pass.set_vertex_buffer(1, uv_mesh.instance_buffer.slice(..));
-                        if let Some(index_buffer) = &uv_mesh.index_buffer {
-                            pass.set_index_buffer(
-                                index_buffer.slice(..),
-                                wgpu::IndexFormat::Uint16,
-                            );
-                            pass.draw_indexed(
-                                0..uv_mesh.index_count as u32,
-                                0,
-                                0..uv_mesh.instances.len() as u32,
-                            );
-                        } else {
-                            pass.draw(0..uv_mesh.index_count as u32, 0..uv_mesh.index_count as u32);
-                        }
-                    }
-                }
+        // Submit the work!
+        self.staging_belt.finish();
+        encoder.pop_debug_group();
+    }
+    /// submit the work to the queue and get the staging belt ready for the next frame
+    fn submit(&mut self, encoder: wgpu::CommandEncoder) {
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        self.local_spawner
+            .spawn(self.staging_belt.recall())
+            .expect("Recall staging belt");
+
+        self.local_pool.run_until_stalled();
+    }
+    /// draw the scene, the shapes and all paragraphs into the given views and submit the work to the queue
+    /// the shadows and render textures are only drawn once, so every view gets the same frame
+    pub(crate) fn render_to_views(&mut self, views: &[&wgpu::TextureView]) {
+        self.write_lights();
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        self.shadow_passes(&mut encoder);
+
+        // render textures are drawn before the screen in the order they were added, so the screen shows what they see this frame
+        // a render texture that shows one added before it sees this frame, one added after it the previous frame
+        encoder.push_debug_group("render texture passes");
+        let first_render_texture = 1 + self.viewports.len();
+        let render_textures = self
+            .render_texture_order
+            .iter()
+            .filter_map(|&key| Some((key, self.render_textures.get(key)?)));
+        for (uniforms, (key, render_texture)) in render_textures.enumerate() {
+            let (width, height) = render_texture.size();
+            self.forward_pass(
+                &mut encoder,
+                ForwardTarget {
+                    color: render_texture
+                        .msaa_color
+                        .as_ref()
+                        .unwrap_or(&render_texture.color.view),
+                    resolve_target: render_texture
+                        .msaa_color
+                        .as_ref()
+                        .map(|_| &render_texture.color.view),
+                    depth: &render_texture.depth,
+                    clear: true,
+                    uniforms: first_render_texture + uniforms,
+                    rect: PixelRect {
+                        x: 0,
+                        y: 0,
+                        width,
+                        height,
+                    },
+                    layer_mask: render_texture.layer_mask,
+                    render_texture: Some(key),
+                },
+            );
+        }
+        encoder.pop_debug_group();
+
+        // forward passes, one for every view
+        // the main view is drawn first, the viewports are drawn on top of it in the order they were added
+        encoder.push_debug_group("forward rendering pass");
+        let mut visible_views = self.visible_views();
+        if visible_views.is_empty() {
+            // nothing is visible, the screen still has to be cleared
+            let size = (self.sc_desc.width, self.sc_desc.height);
+            if let Some(rect) = PixelRect::from_normalized([0.0, 0.0, 1.0, 1.0], size) {
+                visible_views.push((0, rect, 0));
             }
-            if !self.models.is_empty() {
-                pass.set_pipeline(&self.passes.model_forward_pass.pipeline);
-                pass.set_bind_group(0, &self.passes.model_forward_pass.bind_group, &[]); // the globals
-                for model in self.models.iter().flatten() {
-                    if !model.is_empty() {
-                        for mesh in &model.meshes {
-                            pass.set_bind_group(1, &model.materials[mesh.material].bind_group, &[]);
-                            pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
-                            pass.set_vertex_buffer(1, model.instance_buffer.slice(..));
-                            pass.set_index_buffer(
-                                mesh.index_buffer.slice(..),
-                                wgpu::IndexFormat::Uint32,
-                            );
-                            pass.draw_indexed(
-                                0..mesh.indices.len() as u32,
-                                0,
-                                0..model.instances_in_buffer() as u32,
-                            );
-                        }
-                    }
+        }
+        // the textures are missing until the window had an area, then there is no frame to draw into either
+        if let Some(textures) = &self.forward_textures {
+            for &view in views {
+                for (n, &(uniforms, rect, layer_mask)) in visible_views.iter().enumerate() {
+                    self.forward_pass(
+                        &mut encoder,
+                        ForwardTarget {
+                            // with msaa we draw into the multisampled texture and resolve it to the screen
+                            color: textures.msaa_color.as_ref().unwrap_or(view),
+                            resolve_target: textures.msaa_color.as_ref().map(|_| view),
+                            depth: &textures.depth,
+                            // only the first view clears the screen, the others are drawn on top of it
+                            clear: n == 0,
+                            uniforms,
+                            rect,
+                            layer_mask,
+                            render_texture: None,
+                        },
+                    );
                 }
             }
-            if !self.lines.is_empty() {
-                pass.set_pipeline(&self.passes.line_forward_pass.pipeline);
-                pass.set_bind_group(0, &self.passes.line_forward_pass.bind_group, &[]);
-                pass.set_vertex_buffer(0, self.lines.vertex_buffer.slice(..));
-                pass.draw(0..self.lines.number_of_vertices() as u32, 0..1);
-            }
         }
         encoder.pop_debug_group();
 
+        let size = (self.sc_desc.width, self.sc_desc.height);
+        for &view in views {
+            self.shape_pass(&mut encoder, view, size, None);
+            self.text_pass(&mut encoder, view, size, None);
+        }
+        self.submit(encoder);
+    }
+    /// draw the scene seen by the camera of an extra window into it and present the frame
+    /// the shadows are drawn again, so the window is correct even if the main window was not rendered
+    pub(crate) fn render_extra_window(&mut self, key: SlotKey) -> Result<(), RendererError> {
+        // the uniforms of the windows come after those of the viewports and render textures
+        let uniforms = 1
+            + self.viewports.len()
+            + self.render_textures.len()
+            + self
+                .windows
+                .iter_with_keys()
+                .take_while(|(other, _)| *other != key)
+                .count();
+        let window = self
+            .windows
+            .get_mut(key)
+            .ok_or(RendererError::InvalidHandle)?;
+        let (width, height) = window.size();
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        let frame = match acquire_frame(&mut window.target, &self.device, &window.sc_desc)? {
+            Some(frame) => frame,
+            None => return Ok(()),
+        };
 
-        if !self.paragraphs.is_empty() {
-            encoder.push_debug_group("text rendering");
-            {
-                for paragraph in &self.paragraphs {
-                    self.glyph_brush.queue(Section {
-                        screen_position: (paragraph.position.x, paragraph.position.y),
-                        bounds: (self.sc_desc.width as f32, self.sc_desc.height as f32),
-                        layout: Layout::Wrap {
-                            line_breaker: BuiltInLineBreaker::AnyCharLineBreaker,
-                            h_align: paragraph.horizontal_alignment,
-                            v_align: paragraph.vertical_alignment,
-                        },
-                        text: paragraph
-                            .sections
-                            .iter()
-                            .map(|section| {
-                                Text::new(&section.text)
-                                    .with_color(section.color)
-                                    .with_scale(section.scale)
-                                    .with_font_id(section.font)
-                            })
-                            .collect(),
-                    });
-                }
+        self.write_lights();
 
-                // Draw the text!
-                self.glyph_brush
-                    .draw_queued(
-                        &self.device,
-                        &mut self.staging_belt,
-                        &mut encoder,
-                        &frame.output.view,
-                        self.sc_desc.width,
-                        self.sc_desc.height,
-                    )
-                    .expect("Draw queued");
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
 
-                // Submit the work!
-                self.staging_belt.finish();
-            }
-            encoder.pop_debug_group();
-        }
-        self.queue.submit(std::iter::once(encoder.finish()));
+        self.shadow_passes(&mut encoder);
 
-        self.local_spawner
-            .spawn(self.staging_belt.recall())
-            .expect("Recall staging belt");
+        encoder.push_debug_group("forward rendering pass");
+        if let Some(ExtraWindow {
+            textures: Some(textures),
+            layer_mask,
+            ..
+        }) = self.windows.get(key)
+        {
+            self.forward_pass(
+                &mut encoder,
+                ForwardTarget {
+                    color: textures.msaa_color.as_ref().unwrap_or(frame.view()),
+                    resolve_target: textures.msaa_color.as_ref().map(|_| frame.view()),
+                    depth: &textures.depth,
+                    clear: true,
+                    uniforms,
+                    rect: PixelRect {
+                        x: 0,
+                        y: 0,
+                        width,
+                        height,
+                    },
+                    layer_mask: *layer_mask,
+                    render_texture: None,
+                },
+            );
+        }
+        encoder.pop_debug_group();
 
-        self.local_pool.run_until_stalled();
+        self.shape_pass(&mut encoder, frame.view(), (width, height), Some(key));
+        self.text_pass(&mut encoder, frame.view(), (width, height), Some(key));
+        self.submit(encoder);
+        Ok(())
     }
 }
@@ -8,8 +8,9 @@ pub fn create_color_mesh_pipelines(
     shadow_view: &TextureView,
     shadow_sampler: &Sampler,
     sc_desc: &SwapChainDescriptor,
+    sample_count: u32,
     shaders: &ShaderCompiler,
-) -> (Pass, Pass) {
+) -> (Pass, Pass, PipelineCache) {
     let shader = shaders.get_shader("color_mesh");
 
     let uniform_size = std::mem::size_of::<GlobalUniforms>() as wgpu::BufferAddress;
@@ -170,37 +171,24 @@ pub fn create_color_mesh_pipelines(
         label: None,
     });
 
-    // Create the render pipeline
-    let forward_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-        label: Some(&format!("forward pipeline: {}", "color mesh")),
-        layout: Some(&forward_pipeline_layout),
-        vertex: wgpu::VertexState {
-            module: shader,
+    // the pipeline of the default render state is the one of the pass, the others are created when a mesh needs them
+    let pipelines = PipelineCache::new(
+        device,
+        "color mesh",
+        forward_pipeline_layout,
+        ShaderEntry {
+            shader: "color_mesh",
             entry_point: "vs_main",
-            buffers: &[ColorVertex::desc(), ColorInstanceRaw::desc()],
         },
-        fragment: Some(wgpu::FragmentState {
-            module: shader,
+        ShaderEntry {
+            shader: "color_mesh",
             entry_point: "fs_main",
-            targets: &[sc_desc.format.into()],
-        }),
-        primitive: wgpu::PrimitiveState {
-            front_face: wgpu::FrontFace::Ccw,
-            cull_mode: wgpu::CullMode::Back,
-            polygon_mode: wgpu::PolygonMode::Fill,
-            topology: wgpu::PrimitiveTopology::TriangleList,
-            ..Default::default()
         },
-        depth_stencil: Some(wgpu::DepthStencilState {
-            format: DEPTH_FORMAT,
-            depth_write_enabled: true,
-            depth_compare: wgpu::CompareFunction::Less,
-            stencil: wgpu::StencilState::default(),
-            bias: wgpu::DepthBiasState::default(),
-            clamp_depth: false,
-        }),
-        multisample: wgpu::MultisampleState::default(),
-    });
+        vec![ColorVertex::desc(), ColorInstanceRaw::desc()],
+        sc_desc,
+        sample_count,
+    );
+    let forward_pipeline = pipelines.create(device, shaders, PipelineKey::default());
 
     (
         Pass {
@@ -215,5 +203,6 @@ pub fn create_color_mesh_pipelines(
             bind_group: forward_bind_group,
             uniform_buf: forward_uniform_buffer,
         },
+        pipelines,
     )
 }
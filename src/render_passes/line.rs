@@ -6,6 +6,7 @@ pub fn create_line_pipelines(
     device: &Device,
     global_uniforms: &GlobalUniforms,
     sc_desc: &SwapChainDescriptor,
+    sample_count: u32,
     shaders: &ShaderCompiler,
 ) -> (Pass, Pass) {
     let uniform_size = std::mem::size_of::<GlobalUniforms>() as wgpu::BufferAddress;
@@ -141,7 +142,10 @@ pub fn create_line_pipelines(
             bias: wgpu::DepthBiasState::default(),
             clamp_depth: false,
         }),
-        multisample: wgpu::MultisampleState::default(),
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        },
     });
 
     (
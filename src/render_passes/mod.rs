@@ -1,8 +1,10 @@
 use wgpu::util::DeviceExt;
 use wgpu::{BindGroupLayout, Buffer, Device, Sampler, SwapChainDescriptor, TextureView};
 
+pub use background::*;
 pub use color::*;
 pub use model::*;
+pub use pipeline_cache::*;
 use shader_compiler::ShaderCompiler;
 pub use uv::*;
 
@@ -11,28 +13,44 @@ use crate::constants::{DEPTH_FORMAT, SHADOW_FORMAT};
 use crate::instance::InstanceRaw;
 use crate::model::Material;
 use crate::render_passes::line::create_line_pipelines;
+use crate::render_passes::point_cloud::create_point_cloud_pipeline;
+use crate::render_passes::shape::create_shape_pipeline;
+use crate::sprites::{Sprite, SpriteInstanceRaw};
 use crate::uniforms::GlobalUniforms;
+use crate::BlendMode;
 use crate::ColorVertex;
 use crate::ModelVertex;
 use crate::{texture, UvVertex};
 
+mod background;
 mod color;
 mod line;
 mod model;
+mod pipeline_cache;
+mod point_cloud;
 pub mod shader_compiler;
 #[cfg(feature = "hot_reload_shader")]
 pub mod shader_reload;
+mod shape;
 mod uv;
 
 pub struct Passes {
     pub color_shadow_pass: Pass,
     pub color_forward_pass: Pass,
+    pub color_pipelines: PipelineCache,
     pub uv_shadow_pass: Pass,
     pub uv_forward_pass: Pass,
+    pub uv_pipelines: PipelineCache,
+    /// the sprites share the forward pass of the uv meshes, only their pipelines are different
+    pub sprite_pipelines: PipelineCache,
     pub model_shadow_pass: Pass,
     pub model_forward_pass: Pass,
+    pub model_pipelines: PipelineCache,
     pub line_shadow_pass: Pass,
     pub line_forward_pass: Pass,
+    pub point_cloud_forward_pass: Pass,
+    pub background_pipeline: wgpu::RenderPipeline,
+    pub shape_pipeline: wgpu::RenderPipeline,
 }
 
 pub struct Pass {
@@ -42,24 +60,6 @@ pub struct Pass {
     pub uniform_buf: wgpu::Buffer,
 }
 
-pub fn create_shadow_bind_group_layout(device: &Device) -> BindGroupLayout {
-    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-        label: Some("global uniform layout"),
-        entries: &[wgpu::BindGroupLayoutEntry {
-            binding: 0, // global
-            visibility: wgpu::ShaderStage::VERTEX,
-            ty: wgpu::BindingType::Buffer {
-                ty: wgpu::BufferBindingType::Uniform,
-                has_dynamic_offset: false,
-                min_binding_size: wgpu::BufferSize::new(
-                    std::mem::size_of::<GlobalUniforms>() as wgpu::BufferAddress
-                ),
-            },
-            count: None,
-        }],
-    })
-}
-
 impl Passes {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -70,6 +70,7 @@ impl Passes {
         shadow_view: &TextureView,
         shadow_sampler: &Sampler,
         sc_desc: &SwapChainDescriptor,
+        sample_count: u32,
         shaders: &ShaderCompiler,
     ) -> Self {
         let color_mesh_pipelines = create_color_mesh_pipelines(
@@ -80,6 +81,7 @@ impl Passes {
             shadow_view,
             shadow_sampler,
             sc_desc,
+            sample_count,
             shaders,
         );
         let uv_mesh_pipelines = create_uv_mesh_pipelines(
@@ -90,9 +92,10 @@ impl Passes {
             shadow_view,
             shadow_sampler,
             sc_desc,
+            sample_count,
             shaders,
         );
-        let (model_shadow_pass, model_forward_pass) = create_model_render_passes(
+        let (model_shadow_pass, model_forward_pass, model_pipelines) = create_model_render_passes(
             device,
             global_uniforms,
             real_lights_storage_buffer,
@@ -100,19 +103,38 @@ impl Passes {
             shadow_view,
             shadow_sampler,
             sc_desc,
+            sample_count,
             shaders,
         );
         let (line_shadow_pass, line_forward_pass) =
-            create_line_pipelines(device, global_uniforms, sc_desc, shaders);
+            create_line_pipelines(device, global_uniforms, sc_desc, sample_count, shaders);
+        let point_cloud_forward_pass = create_point_cloud_pipeline(
+            device,
+            global_uniforms,
+            simple_lights_storage_buffer,
+            sc_desc,
+            sample_count,
+            shaders,
+        );
+        let background_pipeline =
+            create_background_pipeline(device, sc_desc, sample_count, shaders);
+        let shape_pipeline = create_shape_pipeline(device, sc_desc, shaders);
         Self {
             color_shadow_pass: color_mesh_pipelines.0,
             color_forward_pass: color_mesh_pipelines.1,
+            color_pipelines: color_mesh_pipelines.2,
             uv_shadow_pass: uv_mesh_pipelines.0,
             uv_forward_pass: uv_mesh_pipelines.1,
+            uv_pipelines: uv_mesh_pipelines.2,
+            sprite_pipelines: uv_mesh_pipelines.3,
             model_shadow_pass,
             model_forward_pass,
+            model_pipelines,
             line_shadow_pass,
             line_forward_pass,
+            point_cloud_forward_pass,
+            background_pipeline,
+            shape_pipeline,
         }
     }
 }
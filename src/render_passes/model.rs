@@ -9,11 +9,10 @@ pub fn create_model_render_passes(
     shadow_view: &TextureView,
     shadow_sampler: &Sampler,
     sc_desc: &SwapChainDescriptor,
+    sample_count: u32,
     shaders: &ShaderCompiler,
-) -> (Pass, Pass) {
+) -> (Pass, Pass, PipelineCache) {
     let bake_shader = shaders.get_shader("model_bake");
-    let vs_shader = shaders.get_shader("model_vs");
-    let fs_shader = shaders.get_shader("model_fs");
 
     let uniform_size = std::mem::size_of::<GlobalUniforms>() as wgpu::BufferAddress;
     // Create pipeline layout
@@ -174,35 +173,23 @@ pub fn create_model_render_passes(
         ],
         label: None,
     });
-    // Create the render pipeline
-    let forward_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-        label: Some(&format!("forward pipeline: {}", "model_mesh_pipeline")),
-        layout: Some(&forward_pipeline_layout),
-        vertex: wgpu::VertexState {
-            module: vs_shader,
+    let pipelines = PipelineCache::new(
+        device,
+        "model",
+        forward_pipeline_layout,
+        ShaderEntry {
+            shader: "model_vs",
             entry_point: "main",
-            buffers: &[ModelVertex::desc(), InstanceRaw::desc()],
         },
-        fragment: Some(wgpu::FragmentState {
-            module: fs_shader,
+        ShaderEntry {
+            shader: "model_fs",
             entry_point: "main",
-            targets: &[sc_desc.format.into()],
-        }),
-        primitive: wgpu::PrimitiveState {
-            front_face: wgpu::FrontFace::Ccw,
-            cull_mode: wgpu::CullMode::Back,
-            ..Default::default()
         },
-        depth_stencil: Some(wgpu::DepthStencilState {
-            format: DEPTH_FORMAT,
-            depth_write_enabled: true,
-            depth_compare: wgpu::CompareFunction::Less,
-            stencil: wgpu::StencilState::default(),
-            bias: wgpu::DepthBiasState::default(),
-            clamp_depth: false,
-        }),
-        multisample: wgpu::MultisampleState::default(),
-    });
+        vec![ModelVertex::desc(), InstanceRaw::desc()],
+        sc_desc,
+        sample_count,
+    );
+    let forward_pipeline = pipelines.create(device, shaders, PipelineKey::default());
     (
         Pass {
             pipeline: shadow_pipeline,
@@ -216,5 +203,6 @@ pub fn create_model_render_passes(
             bind_group: forward_bind_group,
             uniform_buf: forward_uniform_buffer,
         },
+        pipelines,
     )
 }
@@ -0,0 +1,250 @@
+use super::*;
+use crate::slots::SlotKey;
+use crate::{RenderState, WgpuRenderer};
+use std::collections::HashMap;
+
+/// everything that is different between the forward pipelines of one kind of mesh
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PipelineKey {
+    pub state: RenderState,
+    pub blend_mode: BlendMode,
+    /// only set for strips of indexed meshes, a strip is restarted at the largest index of the format
+    pub strip_index_format: Option<wgpu::IndexFormat>,
+}
+
+/// a shader module loaded into the ShaderCompiler and the function in it that is used
+#[derive(Copy, Clone, Debug)]
+pub struct ShaderEntry {
+    pub shader: &'static str,
+    pub entry_point: &'static str,
+}
+
+/// the forward pipelines of one kind of mesh for every key that was prepared
+/// the pipeline of the default key is not in the cache, it is the pipeline of the forward pass
+pub struct PipelineCache {
+    label: &'static str,
+    layout: wgpu::PipelineLayout,
+    vertex: ShaderEntry,
+    fragment: ShaderEntry,
+    buffers: Vec<wgpu::VertexBufferLayout<'static>>,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    non_fill_polygon_mode: bool,
+    pipelines: HashMap<PipelineKey, wgpu::RenderPipeline>,
+}
+
+impl PipelineCache {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        device: &Device,
+        label: &'static str,
+        layout: wgpu::PipelineLayout,
+        vertex: ShaderEntry,
+        fragment: ShaderEntry,
+        buffers: Vec<wgpu::VertexBufferLayout<'static>>,
+        sc_desc: &SwapChainDescriptor,
+        sample_count: u32,
+    ) -> Self {
+        Self {
+            label,
+            layout,
+            vertex,
+            fragment,
+            buffers,
+            format: sc_desc.format,
+            sample_count,
+            non_fill_polygon_mode: device
+                .features()
+                .contains(wgpu::Features::NON_FILL_POLYGON_MODE),
+            pipelines: HashMap::new(),
+        }
+    }
+    /// the key of a mesh, index_format is None if the mesh has no indices
+    /// polygon modes the device does not support fall back to Fill
+    pub fn key(
+        &self,
+        mut state: RenderState,
+        blend_mode: BlendMode,
+        index_format: Option<wgpu::IndexFormat>,
+    ) -> PipelineKey {
+        if !self.non_fill_polygon_mode {
+            state.polygon_mode = wgpu::PolygonMode::Fill;
+        }
+        let strip_index_format = match state.topology {
+            wgpu::PrimitiveTopology::LineStrip | wgpu::PrimitiveTopology::TriangleStrip => {
+                index_format
+            }
+            _ => None,
+        };
+        PipelineKey {
+            state,
+            blend_mode,
+            strip_index_format,
+        }
+    }
+    pub fn create(
+        &self,
+        device: &Device,
+        shaders: &ShaderCompiler,
+        key: PipelineKey,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(&format!("forward pipeline: {} {:?}", self.label, key)),
+            layout: Some(&self.layout),
+            vertex: wgpu::VertexState {
+                module: shaders.get_shader(self.vertex.shader),
+                entry_point: self.vertex.entry_point,
+                buffers: &self.buffers,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shaders.get_shader(self.fragment.shader),
+                entry_point: self.fragment.entry_point,
+                targets: &[key.blend_mode.color_target(self.format)],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: key.state.topology,
+                strip_index_format: key.strip_index_format,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: key.state.cull_mode,
+                polygon_mode: key.state.polygon_mode,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: key.state.depth_write && !key.blend_mode.is_transparent(),
+                depth_compare: if key.state.depth_test {
+                    wgpu::CompareFunction::Less
+                } else {
+                    wgpu::CompareFunction::Always
+                },
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+                clamp_depth: false,
+            }),
+            multisample: wgpu::MultisampleState {
+                count: self.sample_count,
+                ..Default::default()
+            },
+        })
+    }
+    /// create the pipeline of the key if it does not exist yet
+    pub fn prepare(&mut self, device: &Device, shaders: &ShaderCompiler, key: PipelineKey) {
+        if key != PipelineKey::default() && !self.pipelines.contains_key(&key) {
+            let pipeline = self.create(device, shaders, key);
+            self.pipelines.insert(key, pipeline);
+        }
+    }
+    /// None for the default key and for keys that were not prepared yet
+    pub fn get(&self, key: PipelineKey) -> Option<&wgpu::RenderPipeline> {
+        self.pipelines.get(&key)
+    }
+    /// the pipeline of a key, forward is the pipeline of the forward pass that is used for the default key
+    /// None if the key was not prepared
+    pub fn pipeline<'a>(
+        &'a self,
+        forward: &'a wgpu::RenderPipeline,
+        key: PipelineKey,
+    ) -> Option<&'a wgpu::RenderPipeline> {
+        if key == PipelineKey::default() {
+            Some(forward)
+        } else {
+            self.get(key)
+        }
+    }
+}
+
+impl WgpuRenderer {
+    /// create the pipeline a ColorMesh is drawn with, this is called whenever its key can change
+    pub(crate) fn prepare_color_mesh_pipeline(&mut self, mesh: SlotKey) {
+        if let Some(color_mesh) = self.color_meshes.get(mesh) {
+            let key = self.passes.color_pipelines.key(
+                color_mesh.render_state,
+                color_mesh.blend_mode,
+                color_mesh.indices_format(),
+            );
+            self.passes
+                .color_pipelines
+                .prepare(&self.device, &self.shaders, key);
+        }
+    }
+    /// create the pipeline a UvMesh is drawn with
+    pub(crate) fn prepare_uv_mesh_pipeline(&mut self, mesh: SlotKey) {
+        if let Some(uv_mesh) = self.uv_meshes.get(mesh) {
+            let key = self.passes.uv_pipelines.key(
+                uv_mesh.render_state,
+                uv_mesh.blend_mode,
+                uv_mesh.indices_format(),
+            );
+            self.passes
+                .uv_pipelines
+                .prepare(&self.device, &self.shaders, key);
+        }
+    }
+    /// create the pipeline a sprite is drawn with
+    pub(crate) fn prepare_sprite_pipeline(&mut self, sprite: SlotKey) {
+        if let Some(sprite) = self.sprites.get(sprite) {
+            let key =
+                self.passes
+                    .sprite_pipelines
+                    .key(Sprite::render_state(), sprite.blend_mode, None);
+            self.passes
+                .sprite_pipelines
+                .prepare(&self.device, &self.shaders, key);
+        }
+    }
+    /// create the pipeline a Model is drawn with
+    pub(crate) fn prepare_model_pipeline(&mut self, model: SlotKey) {
+        if let Some(model) = self.models.get(model) {
+            let key = self.passes.model_pipelines.key(
+                model.render_state,
+                BlendMode::Opaque,
+                Some(wgpu::IndexFormat::Uint32),
+            );
+            self.passes
+                .model_pipelines
+                .prepare(&self.device, &self.shaders, key);
+        }
+    }
+    /// create the pipelines of everything that is loaded, the caches are empty after a shader was reloaded
+    #[cfg(feature = "hot_reload_shader")]
+    pub(crate) fn prepare_all_pipelines(&mut self) {
+        for color_mesh in self.color_meshes.iter() {
+            let key = self.passes.color_pipelines.key(
+                color_mesh.render_state,
+                color_mesh.blend_mode,
+                color_mesh.indices_format(),
+            );
+            self.passes
+                .color_pipelines
+                .prepare(&self.device, &self.shaders, key);
+        }
+        for uv_mesh in self.uv_meshes.iter() {
+            let key = self.passes.uv_pipelines.key(
+                uv_mesh.render_state,
+                uv_mesh.blend_mode,
+                uv_mesh.indices_format(),
+            );
+            self.passes
+                .uv_pipelines
+                .prepare(&self.device, &self.shaders, key);
+        }
+        for sprite in self.sprites.iter() {
+            let key =
+                self.passes
+                    .sprite_pipelines
+                    .key(Sprite::render_state(), sprite.blend_mode, None);
+            self.passes
+                .sprite_pipelines
+                .prepare(&self.device, &self.shaders, key);
+        }
+        for model in self.models.iter() {
+            let key = self.passes.model_pipelines.key(
+                model.render_state,
+                BlendMode::Opaque,
+                Some(wgpu::IndexFormat::Uint32),
+            );
+            self.passes
+                .model_pipelines
+                .prepare(&self.device, &self.shaders, key);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use super::*;
+use crate::point_cloud::{create_point_cloud_bind_group_layout, Point};
+
+/// point clouds are not drawn into the shadow textures, so there is only a forward pass
+pub fn create_point_cloud_pipeline(
+    device: &Device,
+    global_uniforms: &GlobalUniforms,
+    simple_lights_storage_buffer: &Buffer,
+    sc_desc: &SwapChainDescriptor,
+    sample_count: u32,
+    shaders: &ShaderCompiler,
+) -> Pass {
+    let forward_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0, // global
+                    visibility: wgpu::ShaderStage::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            std::mem::size_of::<GlobalUniforms>() as _,
+                        ),
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1, // simple_lights
+                    visibility: wgpu::ShaderStage::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: None,
+        });
+    let forward_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some(&format!("forward pipeline layout: {}", "point cloud")),
+        bind_group_layouts: &[
+            &forward_bind_group_layout,
+            &create_point_cloud_bind_group_layout(device),
+        ],
+        push_constant_ranges: &[],
+    });
+
+    let forward_uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Uniform Buffer"),
+        contents: bytemuck::bytes_of(global_uniforms),
+        usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+    });
+
+    let forward_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &forward_bind_group_layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: forward_uniform_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: simple_lights_storage_buffer.as_entire_binding(),
+            },
+        ],
+        label: None,
+    });
+
+    let shader = shaders.get_shader("point_cloud");
+    let forward_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(&format!("forward pipeline: {}", "point cloud")),
+        layout: Some(&forward_pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[Point::corner_desc(), Point::desc()],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: "fs_main",
+            targets: &[sc_desc.format.into()],
+        }),
+        // every point is a quad facing the camera, so nothing has to be culled
+        primitive: wgpu::PrimitiveState {
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: wgpu::CullMode::None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            topology: wgpu::PrimitiveTopology::TriangleStrip,
+            ..Default::default()
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+            clamp_depth: false,
+        }),
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        },
+    });
+
+    Pass {
+        pipeline: forward_pipeline,
+        bind_group_layout: forward_bind_group_layout,
+        bind_group: forward_bind_group,
+        uniform_buf: forward_uniform_buffer,
+    }
+}
@@ -1,6 +1,7 @@
 use crate::render_passes::shader_compiler::{ShaderPackage, ShaderType};
 use crate::render_passes::{
-    create_color_mesh_pipelines, create_model_render_passes, create_uv_mesh_pipelines,
+    create_background_pipeline, create_color_mesh_pipelines, create_model_render_passes,
+    create_uv_mesh_pipelines,
 };
 use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
 use std::time::Duration;
@@ -8,6 +9,8 @@ use std::path::{Path, PathBuf};
 use std::sync::mpsc::{channel, Receiver};
 use crate::WgpuRenderer;
 use crate::render_passes::line::create_line_pipelines;
+use crate::render_passes::point_cloud::create_point_cloud_pipeline;
+use crate::render_passes::shape::create_shape_pipeline;
 
 pub trait ShaderHotReload {
     fn init_shader_watch(&mut self);
@@ -103,7 +106,7 @@ impl ShaderHotReload for WgpuRenderer {
 
                 match name.as_str() {
                     "color_mesh" => {
-                        let (shadow_pass, forward_pass) = create_color_mesh_pipelines(
+                        let (shadow_pass, forward_pass, pipelines) = create_color_mesh_pipelines(
                             &self.device,
                             &self.global_uniforms,
                             &self.real_lights_storage_buffer,
@@ -111,27 +114,33 @@ impl ShaderHotReload for WgpuRenderer {
                             &self.shadow_view,
                             &self.shadow_sampler,
                             &self.sc_desc,
+                            self.sample_count,
                             &self.shaders,
                         );
                         self.passes.color_shadow_pass = shadow_pass;
                         self.passes.color_forward_pass = forward_pass;
+                        self.passes.color_pipelines = pipelines;
                     }
                     "uv_mesh" => {
-                        let (shadow_pass, forward_pass) = create_uv_mesh_pipelines(
-                            &self.device,
-                            &self.global_uniforms,
-                            &self.real_lights_storage_buffer,
-                            &self.simple_lights_storage_buffer,
-                            &self.shadow_view,
-                            &self.shadow_sampler,
-                            &self.sc_desc,
-                            &self.shaders,
-                        );
+                        let (shadow_pass, forward_pass, pipelines, sprite_pipelines) =
+                            create_uv_mesh_pipelines(
+                                &self.device,
+                                &self.global_uniforms,
+                                &self.real_lights_storage_buffer,
+                                &self.simple_lights_storage_buffer,
+                                &self.shadow_view,
+                                &self.shadow_sampler,
+                                &self.sc_desc,
+                                self.sample_count,
+                                &self.shaders,
+                            );
                         self.passes.uv_shadow_pass = shadow_pass;
                         self.passes.uv_forward_pass = forward_pass;
+                        self.passes.uv_pipelines = pipelines;
+                        self.passes.sprite_pipelines = sprite_pipelines;
                     }
                     "model_bake" | "model_vs" | "model_fs" => {
-                        let (shadow_pass, forward_pass) = create_model_render_passes(
+                        let (shadow_pass, forward_pass, pipelines) = create_model_render_passes(
                             &self.device,
                             &self.global_uniforms,
                             &self.real_lights_storage_buffer,
@@ -139,18 +148,44 @@ impl ShaderHotReload for WgpuRenderer {
                             &self.shadow_view,
                             &self.shadow_sampler,
                             &self.sc_desc,
+                            self.sample_count,
                             &self.shaders,
                         );
                         self.passes.model_shadow_pass = shadow_pass;
                         self.passes.model_forward_pass = forward_pass;
+                        self.passes.model_pipelines = pipelines;
                     }
                     "line_shader" => {
-                        let (line_shadow_pass, line_forward_pass) = create_line_pipelines(&self.device, &self.global_uniforms, &self.sc_desc, &self.shaders);
+                        let (line_shadow_pass, line_forward_pass) = create_line_pipelines(&self.device, &self.global_uniforms, &self.sc_desc, self.sample_count, &self.shaders);
                         self.passes.line_shadow_pass = line_shadow_pass;
                         self.passes.line_forward_pass = line_forward_pass;
                     }
+                    "point_cloud" => {
+                        self.passes.point_cloud_forward_pass = create_point_cloud_pipeline(
+                            &self.device,
+                            &self.global_uniforms,
+                            &self.simple_lights_storage_buffer,
+                            &self.sc_desc,
+                            self.sample_count,
+                            &self.shaders,
+                        );
+                    }
+                    "background" => {
+                        self.passes.background_pipeline = create_background_pipeline(
+                            &self.device,
+                            &self.sc_desc,
+                            self.sample_count,
+                            &self.shaders,
+                        );
+                    }
+                    "shape" => {
+                        self.passes.shape_pipeline =
+                            create_shape_pipeline(&self.device, &self.sc_desc, &self.shaders);
+                    }
                     _ => {}
                 }
+                // the new caches only hold the pipelines of the default keys
+                self.prepare_all_pipelines();
             }
         }
     }
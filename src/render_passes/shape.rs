@@ -0,0 +1,42 @@
+use super::*;
+use crate::shapes::ShapeVertex;
+
+/// the shapes are drawn straight into the resolved view after the scene, like the text
+/// so the pipeline has no depth buffer and is never multisampled
+pub fn create_shape_pipeline(
+    device: &Device,
+    sc_desc: &SwapChainDescriptor,
+    shaders: &ShaderCompiler,
+) -> wgpu::RenderPipeline {
+    let shader = shaders.get_shader("shape");
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("shape pipeline layout"),
+        bind_group_layouts: &[],
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("shape pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[ShapeVertex::desc()],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: "fs_main",
+            targets: &[BlendMode::Alpha.color_target(sc_desc.format)],
+        }),
+        primitive: wgpu::PrimitiveState {
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: wgpu::CullMode::None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            ..Default::default()
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+    })
+}
@@ -9,8 +9,9 @@ pub fn create_uv_mesh_pipelines(
     shadow_view: &TextureView,
     shadow_sampler: &Sampler,
     sc_desc: &SwapChainDescriptor,
+    sample_count: u32,
     shaders: &ShaderCompiler,
-) -> (Pass, Pass) {
+) -> (Pass, Pass, PipelineCache, PipelineCache) {
     // Create pipeline layout
     let shadow_bind_group_layout = create_shadow_bind_group_layout(device);
     let shadow_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -168,35 +169,51 @@ pub fn create_uv_mesh_pipelines(
         label: None,
     });
 
-    // Create the render pipeline
-    let forward_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-        label: Some(&format!("forward pipeline: {}", "uv mesh")),
-        layout: Some(&forward_pipeline_layout),
-        vertex: wgpu::VertexState {
-            module: shader,
+    let pipelines = PipelineCache::new(
+        device,
+        "uv mesh",
+        forward_pipeline_layout,
+        ShaderEntry {
+            shader: "uv_mesh",
             entry_point: "vs_main",
-            buffers: &[UvVertex::desc(), InstanceRaw::desc()],
         },
-        fragment: Some(wgpu::FragmentState {
-            module: shader,
+        ShaderEntry {
+            shader: "uv_mesh",
             entry_point: "fs_main",
-            targets: &[sc_desc.format.into()],
-        }),
-        primitive: wgpu::PrimitiveState {
-            front_face: wgpu::FrontFace::Ccw,
-            cull_mode: wgpu::CullMode::Back,
-            ..Default::default()
         },
-        depth_stencil: Some(wgpu::DepthStencilState {
-            format: DEPTH_FORMAT,
-            depth_write_enabled: true,
-            depth_compare: wgpu::CompareFunction::Less,
-            stencil: wgpu::StencilState::default(),
-            bias: wgpu::DepthBiasState::default(),
-            clamp_depth: false,
-        }),
-        multisample: wgpu::MultisampleState::default(),
+        vec![UvVertex::desc(), InstanceRaw::desc()],
+        sc_desc,
+        sample_count,
+    );
+    let forward_pipeline = pipelines.create(device, shaders, PipelineKey::default());
+
+    // the sprites use the same shader with another vertex stage, they are bound like the uv meshes
+    let sprite_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some(&format!("forward pipeline layout: {}", "sprite")),
+        bind_group_layouts: &[
+            &forward_bind_group_layout,
+            &texture::create_default_bind_group_layout(device),
+        ],
+        push_constant_ranges: &[],
     });
+    let mut sprite_pipelines = PipelineCache::new(
+        device,
+        "sprite",
+        sprite_pipeline_layout,
+        ShaderEntry {
+            shader: "uv_mesh",
+            entry_point: "vs_sprite",
+        },
+        ShaderEntry {
+            shader: "uv_mesh",
+            entry_point: "fs_main",
+        },
+        vec![SpriteInstanceRaw::corner_desc(), SpriteInstanceRaw::desc()],
+        sc_desc,
+        sample_count,
+    );
+    let opaque_sprites = sprite_pipelines.key(Sprite::render_state(), BlendMode::Opaque, None);
+    sprite_pipelines.prepare(device, shaders, opaque_sprites);
 
     (
         Pass {
@@ -211,5 +228,7 @@ pub fn create_uv_mesh_pipelines(
             bind_group: forward_bind_group,
             uniform_buf: forward_uniform_buffer,
         },
+        pipelines,
+        sprite_pipelines,
     )
 }
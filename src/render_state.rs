@@ -0,0 +1,69 @@
+/// how the triangles of a mesh are assembled, rasterized and depth tested
+/// every combination that is used gets its own pipeline, it is created when a mesh starts using it
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RenderState {
+    /// how the vertices or indices are put together, strips use the index format of the mesh
+    pub topology: wgpu::PrimitiveTopology,
+    /// Line and Point need a device with NON_FILL_POLYGON_MODE, without it they are drawn filled
+    pub polygon_mode: wgpu::PolygonMode,
+    /// the side of the triangles that is not drawn, front faces are counter clockwise
+    pub cull_mode: wgpu::CullMode,
+    /// hide what is behind other meshes, without it the mesh is drawn on top of everything before it
+    pub depth_test: bool,
+    /// hide what is drawn later behind this mesh, transparent meshes never write depth
+    pub depth_write: bool,
+}
+
+impl Default for RenderState {
+    fn default() -> Self {
+        Self {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            cull_mode: wgpu::CullMode::Back,
+            depth_test: true,
+            depth_write: true,
+        }
+    }
+}
+
+impl RenderState {
+    /// only the edges of the triangles are drawn
+    pub fn wireframe() -> Self {
+        Self {
+            polygon_mode: wgpu::PolygonMode::Line,
+            cull_mode: wgpu::CullMode::None,
+            ..Self::default()
+        }
+    }
+    /// both sides of the triangles are drawn, for single quads like leaves and grass
+    pub fn double_sided() -> Self {
+        Self {
+            cull_mode: wgpu::CullMode::None,
+            ..Self::default()
+        }
+    }
+    pub fn topology(mut self, topology: wgpu::PrimitiveTopology) -> Self {
+        self.topology = topology;
+        self
+    }
+    pub fn polygon_mode(mut self, polygon_mode: wgpu::PolygonMode) -> Self {
+        self.polygon_mode = polygon_mode;
+        self
+    }
+    pub fn cull_mode(mut self, cull_mode: wgpu::CullMode) -> Self {
+        self.cull_mode = cull_mode;
+        self
+    }
+    pub fn depth_test(mut self, depth_test: bool) -> Self {
+        self.depth_test = depth_test;
+        self
+    }
+    pub fn depth_write(mut self, depth_write: bool) -> Self {
+        self.depth_write = depth_write;
+        self
+    }
+    /// the shadow pipelines only draw triangle lists, everything else does not cast shadows
+    pub(crate) fn casts_shadows(&self) -> bool {
+        self.topology == wgpu::PrimitiveTopology::TriangleList
+    }
+}
@@ -0,0 +1,174 @@
+use crate::constants::DEPTH_FORMAT;
+use wgpu::{Device, SwapChainDescriptor, SwapChainFrame, TextureView};
+
+/// the place the final image of every frame is written to
+pub enum RenderTarget {
+    /// a window, frames are presented through the swap chain
+    /// the swap chain is only created once the window has an area
+    Window {
+        surface: wgpu::Surface,
+        swap_chain: Option<wgpu::SwapChain>,
+    },
+    /// an offscreen texture, used when rendering without a window
+    Texture(wgpu::Texture),
+}
+
+/// a single frame acquired from a RenderTarget
+/// dropping a frame of a window presents it
+pub enum Frame {
+    Window(SwapChainFrame),
+    Texture(TextureView),
+}
+
+impl Frame {
+    pub fn view(&self) -> &TextureView {
+        match self {
+            Frame::Window(frame) => &frame.output.view,
+            Frame::Texture(view) => view,
+        }
+    }
+}
+
+impl RenderTarget {
+    pub fn window(device: &Device, surface: wgpu::Surface, sc_desc: &SwapChainDescriptor) -> Self {
+        let swap_chain = create_swap_chain(device, &surface, sc_desc);
+        RenderTarget::Window {
+            surface,
+            swap_chain,
+        }
+    }
+    pub fn offscreen(device: &Device, sc_desc: &SwapChainDescriptor) -> Self {
+        RenderTarget::Texture(create_offscreen_texture(device, sc_desc))
+    }
+    pub fn next_frame(&self) -> Result<Frame, wgpu::SwapChainError> {
+        Ok(match self {
+            RenderTarget::Window { swap_chain, .. } => match swap_chain {
+                Some(swap_chain) => Frame::Window(swap_chain.get_current_frame()?),
+                // rebuilding creates the swap chain as soon as the window has an area
+                None => return Err(wgpu::SwapChainError::Outdated),
+            },
+            RenderTarget::Texture(texture) => {
+                Frame::Texture(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+            }
+        })
+    }
+    /// recreate the swap chain or the offscreen texture after the size has changed
+    pub fn rebuild(&mut self, device: &Device, sc_desc: &SwapChainDescriptor) {
+        match self {
+            RenderTarget::Window {
+                surface,
+                swap_chain,
+            } => {
+                if let Some(new) = create_swap_chain(device, surface, sc_desc) {
+                    *swap_chain = Some(new);
+                }
+            }
+            RenderTarget::Texture(texture) => {
+                *texture = create_offscreen_texture(device, sc_desc);
+            }
+        }
+    }
+}
+
+/// None while the window has no area, a swap chain can't have a size of 0
+fn create_swap_chain(
+    device: &Device,
+    surface: &wgpu::Surface,
+    sc_desc: &SwapChainDescriptor,
+) -> Option<wgpu::SwapChain> {
+    if sc_desc.width == 0 || sc_desc.height == 0 {
+        return None;
+    }
+    Some(device.create_swap_chain(surface, sc_desc))
+}
+
+fn create_offscreen_texture(device: &Device, sc_desc: &SwapChainDescriptor) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("offscreen target"),
+        size: wgpu::Extent3d {
+            width: sc_desc.width,
+            height: sc_desc.height,
+            depth: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: sc_desc.format,
+        usage: sc_desc.usage,
+    })
+}
+
+/// the textures the forward pass draws into next to the frame itself
+/// they have the size of the frame, so they can't exist while a window has no area
+pub struct ForwardTextures {
+    pub depth: TextureView,
+    /// the multisampled color target, None when there is only one sample per pixel
+    pub msaa_color: Option<TextureView>,
+}
+
+impl ForwardTextures {
+    /// None while the size in the descriptor is 0
+    pub fn new(device: &Device, sc_desc: &SwapChainDescriptor, sample_count: u32) -> Option<Self> {
+        if sc_desc.width == 0 || sc_desc.height == 0 {
+            return None;
+        }
+        Some(Self {
+            depth: create_depth_view(device, sc_desc, sample_count),
+            msaa_color: create_msaa_view(device, sc_desc, sample_count),
+        })
+    }
+}
+
+/// create the depth buffer of the forward pass
+/// it needs the same number of samples as the color attachment it is used with
+pub fn create_depth_view(
+    device: &Device,
+    sc_desc: &SwapChainDescriptor,
+    sample_count: u32,
+) -> TextureView {
+    device
+        .create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: sc_desc.width,
+                height: sc_desc.height,
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
+            label: Some("forward depth"),
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+/// create the multisampled color texture the forward pass draws into
+/// it is resolved into the frame at the end of the pass
+/// with a single sample there is nothing to resolve, the frame is drawn into directly
+pub fn create_msaa_view(
+    device: &Device,
+    sc_desc: &SwapChainDescriptor,
+    sample_count: u32,
+) -> Option<TextureView> {
+    if sample_count <= 1 {
+        return None;
+    }
+    Some(
+        device
+            .create_texture(&wgpu::TextureDescriptor {
+                size: wgpu::Extent3d {
+                    width: sc_desc.width,
+                    height: sc_desc.height,
+                    depth: 1,
+                },
+                mip_level_count: 1,
+                sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format: sc_desc.format,
+                usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
+                label: Some("msaa color"),
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default()),
+    )
+}
@@ -0,0 +1,121 @@
+use crate::render_target::{create_depth_view, create_msaa_view};
+use crate::slots::SlotKey;
+use crate::texture::Texture;
+use crate::{Camera, ALL_LAYERS};
+use wgpu::{Device, SwapChainDescriptor, TextureView};
+
+/// a camera that renders the scene into a texture instead of the screen
+/// the texture can be shown on a UvMesh with set_uv_mesh_render_texture
+/// the aspect ratio of the camera is set by the renderer to match the texture
+pub struct RenderTexture {
+    pub camera: Camera,
+    /// only meshes and models that are in at least one of these layers are drawn
+    pub layer_mask: u32,
+    pub(crate) color: Texture,
+    pub(crate) msaa_color: Option<TextureView>,
+    pub(crate) depth: TextureView,
+}
+
+impl RenderTexture {
+    /// the color texture uses the format of the screen so the forward pipelines can draw into it
+    pub(crate) fn new(
+        device: &Device,
+        camera: Camera,
+        size: (u32, u32),
+        sc_desc: &SwapChainDescriptor,
+        sample_count: u32,
+    ) -> Self {
+        let (color, msaa_color, depth) = create_targets(device, size, sc_desc, sample_count);
+        Self {
+            camera,
+            layer_mask: ALL_LAYERS,
+            color,
+            msaa_color,
+            depth,
+        }
+    }
+    /// recreate all textures with a new size
+    /// anything that samples the old color texture has to be bound again
+    pub(crate) fn resize(
+        &mut self,
+        device: &Device,
+        size: (u32, u32),
+        sc_desc: &SwapChainDescriptor,
+        sample_count: u32,
+    ) {
+        let (color, msaa_color, depth) = create_targets(device, size, sc_desc, sample_count);
+        self.color = color;
+        self.msaa_color = msaa_color;
+        self.depth = depth;
+    }
+    pub fn size(&self) -> (u32, u32) {
+        self.color.size
+    }
+    pub(crate) fn aspect(&self) -> f32 {
+        self.color.size.0 as f32 / self.color.size.1 as f32
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RenderTextureHandle {
+    key: SlotKey,
+}
+impl RenderTextureHandle {
+    pub(crate) fn new(key: SlotKey) -> Self {
+        Self { key }
+    }
+    pub(crate) fn key(&self) -> SlotKey {
+        self.key
+    }
+}
+
+fn create_targets(
+    device: &Device,
+    size: (u32, u32),
+    sc_desc: &SwapChainDescriptor,
+    sample_count: u32,
+) -> (Texture, Option<TextureView>, TextureView) {
+    let size = (size.0.max(1), size.1.max(1));
+    let desc = SwapChainDescriptor {
+        width: size.0,
+        height: size.1,
+        ..sc_desc.clone()
+    };
+    (
+        create_color_texture(device, size, sc_desc.format),
+        create_msaa_view(device, &desc, sample_count),
+        create_depth_view(device, &desc, sample_count),
+    )
+}
+
+fn create_color_texture(device: &Device, size: (u32, u32), format: wgpu::TextureFormat) -> Texture {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("render texture"),
+        size: wgpu::Extent3d {
+            width: size.0,
+            height: size.1,
+            depth: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        ..Default::default()
+    });
+    Texture {
+        texture,
+        view,
+        sampler,
+        size,
+    }
+}
@@ -1,5 +1,6 @@
 use crate::WgpuRenderer;
-use crate::constants::{generate_matrix, DEPTH_FORMAT};
+use crate::constants::generate_matrix;
+use crate::render_target::ForwardTextures;
 
 pub trait Resize {
     fn resize(&mut self, size: (i32, i32));
@@ -7,8 +8,16 @@ pub trait Resize {
 
 impl Resize for WgpuRenderer {
     fn resize(&mut self, size: (i32, i32)) {
-        self.sc_desc.width = size.0.max(1) as u32;
-        self.sc_desc.height = size.1.max(1) as u32;
+        self.sc_desc.width = size.0.max(0) as u32;
+        self.sc_desc.height = size.1.max(0) as u32;
+        // the offscreen target is recreated with the new size, its old frame can't be captured anymore
+        self.frame_rendered = false;
+
+        // a minimized window has a size of 0x0, nothing can be created with that size
+        // the old textures are kept until the window gets an area again and nothing is rendered in between
+        if self.sc_desc.width == 0 || self.sc_desc.height == 0 {
+            return;
+        }
 
         self.camera.set_aspect_ratio(self.aspect());
 
@@ -20,21 +29,9 @@ impl Resize for WgpuRenderer {
             bytemuck::cast_slice(&mx_total),
         );
 
-        let depth_texture = self.device.create_texture(&wgpu::TextureDescriptor {
-            size: wgpu::Extent3d {
-                width: self.sc_desc.width,
-                height: self.sc_desc.height,
-                depth: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: DEPTH_FORMAT,
-            usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
-            label: None,
-        });
-        self.forward_depth = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.forward_textures =
+            ForwardTextures::new(&self.device, &self.sc_desc, self.sample_count);
 
-        self.swap_chain = self.device.create_swap_chain(&self.surface, &self.sc_desc);
+        self.target.rebuild(&self.device, &self.sc_desc);
     }
 }
\ No newline at end of file
@@ -0,0 +1,7 @@
+pub use shape::*;
+pub(crate) use tessellate::*;
+pub(crate) use vertex::*;
+
+mod shape;
+mod tessellate;
+mod vertex;
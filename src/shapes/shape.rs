@@ -0,0 +1,77 @@
+use cgmath::Vector2;
+
+/// a 2D shape drawn on top of the scene, below the paragraphs
+/// all positions and sizes are in pixels, measured from the top left corner of the screen
+#[derive(Debug, Clone)]
+pub struct Shape {
+    pub kind: ShapeKind,
+    pub style: ShapeStyle,
+    pub color: [f32; 4],
+    /// shapes with a higher z are drawn on top of those with a lower one
+    /// shapes with the same z are drawn in the order they are in the list
+    pub z: i32,
+}
+
+impl Shape {
+    /// a filled shape with a z of 0
+    pub fn filled(kind: ShapeKind, color: [f32; 4]) -> Self {
+        Self {
+            kind,
+            style: ShapeStyle::Fill,
+            color,
+            z: 0,
+        }
+    }
+    /// the outline of a shape with a z of 0
+    pub fn stroked(kind: ShapeKind, width: f32, color: [f32; 4]) -> Self {
+        Self {
+            kind,
+            style: ShapeStyle::Stroke(width),
+            color,
+            z: 0,
+        }
+    }
+    pub fn with_z(mut self, z: i32) -> Self {
+        self.z = z;
+        self
+    }
+}
+
+/// the geometry of a shape
+#[derive(Debug, Clone)]
+pub enum ShapeKind {
+    /// a rectangle from its top left corner
+    Rect {
+        position: Vector2<f32>,
+        size: Vector2<f32>,
+    },
+    /// a rectangle with round corners, the radius is at most half of the shorter side
+    RoundedRect {
+        position: Vector2<f32>,
+        size: Vector2<f32>,
+        radius: f32,
+    },
+    Circle {
+        center: Vector2<f32>,
+        radius: f32,
+    },
+    /// a part of a circle between two angles in radians
+    /// an angle of 0 points to the right and the angles grow clockwise on screen
+    /// a filled arc is a pie slice, a stroked arc is only the curved line
+    Arc {
+        center: Vector2<f32>,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+    },
+    /// the corners of a polygon in order, it can be concave but must not cross itself
+    Polygon(Vec<Vector2<f32>>),
+}
+
+/// how a shape is drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShapeStyle {
+    Fill,
+    /// an outline with the given width in pixels, centered on the edge of the shape
+    Stroke(f32),
+}
@@ -0,0 +1,458 @@
+use std::f32::consts::PI;
+
+use cgmath::{InnerSpace, Vector2};
+
+use crate::shapes::{Shape, ShapeKind, ShapeStyle, ShapeVertex};
+
+/// the largest distance in pixels between a curve and the straight segments drawn instead of it
+const CURVE_TOLERANCE: f32 = 0.25;
+/// the width in pixels over which the edges fade out
+const FRINGE: f32 = 1.0;
+/// how far the sharp corners of a stroke may reach, in multiples of half its width
+const MITER_LIMIT: f32 = 4.0;
+
+type Corner = (Vector2<f32>, [f32; 4]);
+
+/// turn the shapes into a triangle list for a screen of the given size
+/// the shapes are sorted by z, so drawing the triangles in order puts every shape on top of those below it
+pub(crate) fn tessellate_shapes(shapes: &[Shape], size: (u32, u32)) -> Vec<ShapeVertex> {
+    let mut sorted: Vec<&Shape> = shapes.iter().collect();
+    // the sort is stable, shapes with the same z stay in the order they were added
+    sorted.sort_by_key(|shape| shape.z);
+    let mut triangles = Triangles {
+        vertices: vec![],
+        size,
+    };
+    for shape in sorted {
+        match shape.style {
+            ShapeStyle::Fill => fill(&fill_outline(&shape.kind), shape.color, &mut triangles),
+            ShapeStyle::Stroke(width) => {
+                let (points, closed) = stroke_outline(&shape.kind);
+                stroke(&points, closed, width, shape.color, &mut triangles);
+            }
+        }
+    }
+    triangles.vertices
+}
+
+struct Triangles {
+    vertices: Vec<ShapeVertex>,
+    size: (u32, u32),
+}
+
+impl Triangles {
+    fn triangle(&mut self, corners: [Corner; 3]) {
+        for (position, color) in corners.iter() {
+            self.vertices
+                .push(ShapeVertex::new(*position, *color, self.size));
+        }
+    }
+    /// the corners have to go around the quad
+    fn quad(&mut self, corners: [Corner; 4]) {
+        self.triangle([corners[0], corners[1], corners[2]]);
+        self.triangle([corners[0], corners[2], corners[3]]);
+    }
+}
+
+/// the points around the area covered by a filled shape
+fn fill_outline(kind: &ShapeKind) -> Vec<Vector2<f32>> {
+    match kind {
+        ShapeKind::Rect { position, size } => vec![
+            *position,
+            position + Vector2::new(size.x, 0.0),
+            position + size,
+            position + Vector2::new(0.0, size.y),
+        ],
+        ShapeKind::RoundedRect {
+            position,
+            size,
+            radius,
+        } => {
+            let radius = radius.min(size.x.abs() / 2.0).min(size.y.abs() / 2.0);
+            let (left, top) = (position.x + radius, position.y + radius);
+            let (right, bottom) = (position.x + size.x - radius, position.y + size.y - radius);
+            let mut points = arc_points(Vector2::new(left, top), radius, PI, 1.5 * PI);
+            points.extend(arc_points(
+                Vector2::new(right, top),
+                radius,
+                1.5 * PI,
+                2.0 * PI,
+            ));
+            points.extend(arc_points(
+                Vector2::new(right, bottom),
+                radius,
+                0.0,
+                0.5 * PI,
+            ));
+            points.extend(arc_points(Vector2::new(left, bottom), radius, 0.5 * PI, PI));
+            points
+        }
+        ShapeKind::Circle { center, radius } => arc_points(*center, *radius, 0.0, 2.0 * PI),
+        ShapeKind::Arc {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+        } => {
+            let mut points = arc_points(*center, *radius, *start_angle, *end_angle);
+            if (end_angle - start_angle).abs() < 2.0 * PI {
+                points.push(*center);
+            }
+            points
+        }
+        ShapeKind::Polygon(points) => points.clone(),
+    }
+}
+
+/// the points along the outline of a shape and whether the outline is closed
+fn stroke_outline(kind: &ShapeKind) -> (Vec<Vector2<f32>>, bool) {
+    match kind {
+        ShapeKind::Arc {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+        } => (
+            arc_points(*center, *radius, *start_angle, *end_angle),
+            (end_angle - start_angle).abs() >= 2.0 * PI,
+        ),
+        kind => (fill_outline(kind), true),
+    }
+}
+
+/// points on a circle from the start to the end angle, both included
+/// there are just enough of them that the segments stay within the tolerance of the curve
+fn arc_points(center: Vector2<f32>, radius: f32, start: f32, end: f32) -> Vec<Vector2<f32>> {
+    let radius = radius.max(0.0);
+    let sweep = (end - start).max(-2.0 * PI).min(2.0 * PI);
+    let step = 2.0 * (1.0 - CURVE_TOLERANCE / radius).max(-1.0).acos();
+    let segments = ((sweep.abs() / step).ceil() as usize).clamp(1, 1024);
+    (0..=segments)
+        .map(|segment| {
+            let angle = start + sweep * segment as f32 / segments as f32;
+            center + Vector2::new(angle.cos(), angle.sin()) * radius
+        })
+        .collect()
+}
+
+/// remove points that are on top of the one before them
+fn dedup(points: &[Vector2<f32>], closed: bool) -> Vec<Vector2<f32>> {
+    let mut result: Vec<Vector2<f32>> = Vec::with_capacity(points.len());
+    for point in points {
+        if result
+            .last()
+            .map_or(true, |last| (point - last).magnitude2() > 1e-6)
+        {
+            result.push(*point);
+        }
+    }
+    if closed && result.len() > 1 && (result[0] - result[result.len() - 1]).magnitude2() <= 1e-6 {
+        result.pop();
+    }
+    result
+}
+
+fn cross(a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+/// twice the area of the polygon, positive if the points go around it clockwise on screen
+fn signed_area(points: &[Vector2<f32>]) -> f32 {
+    (0..points.len())
+        .map(|i| cross(points[i], points[(i + 1) % points.len()]))
+        .sum()
+}
+
+/// the direction to move every point in to get an outline that is one pixel further out
+/// for a closed polygon with a positive area these point outwards
+fn miter_normals(points: &[Vector2<f32>], closed: bool) -> Vec<Vector2<f32>> {
+    let len = points.len();
+    let edge_normal = |i: usize| {
+        let edge = points[(i + 1) % len] - points[i];
+        Vector2::new(edge.y, -edge.x).normalize()
+    };
+    (0..len)
+        .map(|i| {
+            let before = if closed || i > 0 {
+                Some(edge_normal((i + len - 1) % len))
+            } else {
+                None
+            };
+            let after = if closed || i + 1 < len {
+                Some(edge_normal(i))
+            } else {
+                None
+            };
+            match (before, after) {
+                (Some(before), Some(after)) => {
+                    let sum = before + after;
+                    // the outline turns back on itself, there is no sensible miter
+                    if sum.magnitude2() < 1e-6 {
+                        return after;
+                    }
+                    let miter = sum.normalize();
+                    miter / miter.dot(after).max(1.0 / MITER_LIMIT)
+                }
+                (Some(normal), None) | (None, Some(normal)) => normal,
+                (None, None) => Vector2::new(0.0, 0.0),
+            }
+        })
+        .collect()
+}
+
+/// split a polygon with a positive area into triangles by clipping its ears
+fn triangulate(points: &[Vector2<f32>]) -> Vec<[usize; 3]> {
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    let mut triangles = Vec::with_capacity(points.len().saturating_sub(2));
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let corners = |i: usize| {
+            [
+                remaining[(i + len - 1) % len],
+                remaining[i],
+                remaining[(i + 1) % len],
+            ]
+        };
+        let ear = (0..len).find(|&i| {
+            let [a, b, c] = corners(i);
+            cross(points[b] - points[a], points[c] - points[b]) > 0.0
+                && !remaining.iter().any(|&other| {
+                    other != a
+                        && other != b
+                        && other != c
+                        && in_triangle(points[other], [points[a], points[b], points[c]])
+                })
+        });
+        // a polygon that crosses itself can run out of ears, the rest of it is cut off anyway
+        let ear = ear.unwrap_or(0);
+        triangles.push(corners(ear));
+        remaining.remove(ear);
+    }
+    if remaining.len() == 3 {
+        triangles.push([remaining[0], remaining[1], remaining[2]]);
+    }
+    triangles
+}
+
+fn in_triangle(point: Vector2<f32>, [a, b, c]: [Vector2<f32>; 3]) -> bool {
+    cross(b - a, point - a) >= 0.0
+        && cross(c - b, point - b) >= 0.0
+        && cross(a - c, point - c) >= 0.0
+}
+
+/// fill the polygon, its edges fade out over one pixel centered on the outline
+fn fill(points: &[Vector2<f32>], color: [f32; 4], triangles: &mut Triangles) {
+    let mut points = dedup(points, true);
+    if points.len() < 3 {
+        return;
+    }
+    if signed_area(&points) < 0.0 {
+        points.reverse();
+    }
+    let transparent = [color[0], color[1], color[2], 0.0];
+    let normals = miter_normals(&points, true);
+    let offset = |distance: f32| -> Vec<Vector2<f32>> {
+        points
+            .iter()
+            .zip(&normals)
+            .map(|(point, normal)| point + normal * distance)
+            .collect()
+    };
+    let inner = offset(-FRINGE / 2.0);
+    let outer = offset(FRINGE / 2.0);
+
+    for [a, b, c] in triangulate(&points) {
+        triangles.triangle([(inner[a], color), (inner[b], color), (inner[c], color)]);
+    }
+    for i in 0..points.len() {
+        let j = (i + 1) % points.len();
+        triangles.quad([
+            (inner[i], color),
+            (inner[j], color),
+            (outer[j], transparent),
+            (outer[i], transparent),
+        ]);
+    }
+}
+
+/// draw a line of the given width along the points, it fades out over one pixel on both sides
+fn stroke(
+    points: &[Vector2<f32>],
+    closed: bool,
+    width: f32,
+    color: [f32; 4],
+    triangles: &mut Triangles,
+) {
+    let points = dedup(points, closed);
+    let closed = closed && points.len() > 2;
+    if points.len() < 2 || width <= 0.0 {
+        return;
+    }
+    // lines thinner than a pixel are drawn as wide as the fringe, but more transparent
+    let color = [color[0], color[1], color[2], color[3] * width.min(1.0)];
+    let transparent = [color[0], color[1], color[2], 0.0];
+    let inner = (width - FRINGE).max(0.0) / 2.0;
+    let outer = inner + FRINGE;
+    let normals = miter_normals(&points, closed);
+    let rails: Vec<[Corner; 4]> = points
+        .iter()
+        .zip(&normals)
+        .map(|(point, normal)| {
+            [
+                (point - normal * outer, transparent),
+                (point - normal * inner, color),
+                (point + normal * inner, color),
+                (point + normal * outer, transparent),
+            ]
+        })
+        .collect();
+    let segments = if closed {
+        points.len()
+    } else {
+        points.len() - 1
+    };
+    for i in 0..segments {
+        let (a, b) = (&rails[i], &rails[(i + 1) % points.len()]);
+        for rail in 0..3 {
+            triangles.quad([a[rail], b[rail], b[rail + 1], a[rail + 1]]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+    const GREEN: [f32; 4] = [0.0, 1.0, 0.0, 1.0];
+    const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+    const SIZE: (u32, u32) = (64, 64);
+
+    fn rect(x: f32, y: f32) -> ShapeKind {
+        ShapeKind::Rect {
+            position: Vector2::new(x, y),
+            size: Vector2::new(8.0, 8.0),
+        }
+    }
+
+    /// the position and the color of every vertex
+    fn floats(vertices: &[ShapeVertex]) -> Vec<[f32; 6]> {
+        vertices
+            .iter()
+            .map(|vertex| bytemuck::cast(*vertex))
+            .collect()
+    }
+
+    #[test]
+    fn shapes_are_sorted_by_z_and_keep_their_order_otherwise() {
+        let shapes = [
+            Shape::filled(rect(0.0, 0.0), RED).with_z(1),
+            Shape::filled(rect(8.0, 0.0), GREEN),
+            Shape::filled(rect(16.0, 0.0), BLUE),
+        ];
+        let mut colors: Vec<[f32; 3]> = vec![];
+        for vertex in floats(&tessellate_shapes(&shapes, SIZE)) {
+            let color = [vertex[2], vertex[3], vertex[4]];
+            if colors.last() != Some(&color) {
+                colors.push(color);
+            }
+        }
+        assert_eq!(
+            colors,
+            vec![[0.0, 1.0, 0.0], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn arcs_without_a_radius_collapse_to_their_center() {
+        let center = Vector2::new(10.0, 20.0);
+        for &radius in &[0.0, -5.0] {
+            let points = arc_points(center, radius, 0.0, PI);
+            assert!(points.len() >= 2);
+            assert!(points.iter().all(|point| *point == center));
+
+            let circle = ShapeKind::Circle { center, radius };
+            let shapes = [
+                Shape::filled(circle.clone(), RED),
+                Shape::stroked(circle, 2.0, RED),
+            ];
+            assert!(tessellate_shapes(&shapes, SIZE).is_empty());
+        }
+    }
+
+    #[test]
+    fn concave_polygon_is_split_into_n_minus_2_triangles() {
+        // an L shape, the top right quarter of the square is cut out
+        let mut points = vec![
+            Vector2::new(8.0, 8.0),
+            Vector2::new(32.0, 8.0),
+            Vector2::new(32.0, 32.0),
+            Vector2::new(56.0, 32.0),
+            Vector2::new(56.0, 56.0),
+            Vector2::new(8.0, 56.0),
+        ];
+        if signed_area(&points) < 0.0 {
+            points.reverse();
+        }
+        let triangles = triangulate(&points);
+        assert_eq!(triangles.len(), points.len() - 2);
+        // every triangle is inside the polygon, so together they cover exactly its area
+        let area: f32 = triangles
+            .iter()
+            .map(|&[a, b, c]| cross(points[b] - points[a], points[c] - points[a]))
+            .inspect(|&area| assert!(area > 0.0))
+            .sum();
+        assert_eq!(area, signed_area(&points));
+    }
+
+    #[test]
+    fn degenerate_outlines_give_no_nans() {
+        let point = Vector2::new(20.0, 20.0);
+        let kinds = vec![
+            ShapeKind::Polygon(vec![]),
+            ShapeKind::Polygon(vec![point, point, point]),
+            // duplicate corners, also between the last and the first one
+            ShapeKind::Polygon(vec![
+                Vector2::new(0.0, 0.0),
+                Vector2::new(0.0, 0.0),
+                Vector2::new(10.0, 0.0),
+                Vector2::new(10.0, 0.0),
+                Vector2::new(10.0, 10.0),
+                Vector2::new(0.0, 0.0),
+            ]),
+            // all corners on one line
+            ShapeKind::Polygon(vec![
+                Vector2::new(0.0, 0.0),
+                Vector2::new(5.0, 0.0),
+                Vector2::new(10.0, 0.0),
+            ]),
+            ShapeKind::Rect {
+                position: point,
+                size: Vector2::new(0.0, 0.0),
+            },
+            ShapeKind::RoundedRect {
+                position: point,
+                size: Vector2::new(0.0, 10.0),
+                radius: 4.0,
+            },
+            ShapeKind::Arc {
+                center: point,
+                radius: 10.0,
+                start_angle: 1.0,
+                end_angle: 1.0,
+            },
+        ];
+        let shapes: Vec<Shape> = kinds
+            .into_iter()
+            .flat_map(|kind| {
+                vec![
+                    Shape::filled(kind.clone(), RED),
+                    Shape::stroked(kind, 2.0, RED),
+                ]
+            })
+            .collect();
+        for vertex in floats(&tessellate_shapes(&shapes, SIZE)) {
+            assert!(vertex.iter().all(|value| value.is_finite()), "{:?}", vertex);
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use bytemuck::{Pod, Zeroable};
+use cgmath::Vector2;
+use wgpu::{VertexBufferLayout, VertexFormat};
+
+/// a vertex of a tessellated shape, the position is in clip space
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub(crate) struct ShapeVertex {
+    position: [f32; 2],
+    color: [f32; 4],
+}
+
+impl ShapeVertex {
+    pub fn desc<'a>() -> VertexBufferLayout<'a> {
+        VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::InputStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    shader_location: 0,
+                    offset: 0,
+                    format: VertexFormat::Float2,
+                },
+                wgpu::VertexAttribute {
+                    shader_location: 1,
+                    offset: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    format: VertexFormat::Float4,
+                },
+            ],
+        }
+    }
+    /// a vertex at the given position in pixels on a screen of the given size
+    pub(crate) fn new(position: Vector2<f32>, color: [f32; 4], size: (u32, u32)) -> Self {
+        Self {
+            position: [
+                position.x / size.0 as f32 * 2.0 - 1.0,
+                1.0 - position.y / size.1 as f32 * 2.0,
+            ],
+            color,
+        }
+    }
+}
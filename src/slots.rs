@@ -0,0 +1,117 @@
+/// points at a value in Slots
+/// the generation tells apart the values that were put into the same slot one after another
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct SlotKey {
+    pub(crate) index: usize,
+    pub(crate) generation: u32,
+}
+
+#[derive(Debug, Clone)]
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+/// a list of values where removing one does not move the others
+/// free slots are reused, but the key of a removed value never points at the value that replaced it
+#[derive(Debug, Clone)]
+pub(crate) struct Slots<T> {
+    slots: Vec<Slot<T>>,
+    /// the indices of the empty slots
+    free: Vec<usize>,
+}
+
+impl<T> Slots<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            slots: vec![],
+            free: vec![],
+        }
+    }
+    /// put the value into a free slot, the slot freed last is used first
+    pub(crate) fn insert(&mut self, value: T) -> SlotKey {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    value: None,
+                });
+                self.slots.len() - 1
+            }
+        };
+        let slot = &mut self.slots[index];
+        slot.value = Some(value);
+        SlotKey {
+            index,
+            generation: slot.generation,
+        }
+    }
+    /// remove the value the key points at, a key that doesn't point at anything is ignored
+    pub(crate) fn remove(&mut self, key: SlotKey) -> Option<T> {
+        let slot = self.slots.get_mut(key.index)?;
+        if slot.generation != key.generation || slot.value.is_none() {
+            return None;
+        }
+        // every key handed out for this slot so far is invalid from now on
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(key.index);
+        slot.value.take()
+    }
+    /// remove all values, every key handed out so far becomes invalid
+    pub(crate) fn clear(&mut self) {
+        self.free.clear();
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if slot.value.take().is_some() {
+                slot.generation = slot.generation.wrapping_add(1);
+            }
+            self.free.push(index);
+        }
+    }
+    pub(crate) fn get(&self, key: SlotKey) -> Option<&T> {
+        self.slots
+            .get(key.index)
+            .filter(|slot| slot.generation == key.generation)
+            .and_then(|slot| slot.value.as_ref())
+    }
+    pub(crate) fn get_mut(&mut self, key: SlotKey) -> Option<&mut T> {
+        self.slots
+            .get_mut(key.index)
+            .filter(|slot| slot.generation == key.generation)
+            .and_then(|slot| slot.value.as_mut())
+    }
+    /// all values in the order of their slots
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &T> {
+        self.slots.iter().filter_map(|slot| slot.value.as_ref())
+    }
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.slots.iter_mut().filter_map(|slot| slot.value.as_mut())
+    }
+    /// all values together with their keys in the order of their slots
+    pub(crate) fn iter_with_keys(&self) -> impl Iterator<Item = (SlotKey, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            slot.value.as_ref().map(|value| {
+                (
+                    SlotKey {
+                        index,
+                        generation: slot.generation,
+                    },
+                    value,
+                )
+            })
+        })
+    }
+    /// the number of values, free slots are not counted
+    pub(crate) fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Default for Slots<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
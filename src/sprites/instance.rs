@@ -0,0 +1,135 @@
+use crate::bounds::{Aabb, BoundingSphere, Bounds};
+use bytemuck::{Pod, Zeroable};
+use cgmath::{InnerSpace, Vector2, Vector3, Vector4};
+use wgpu::{VertexBufferLayout, VertexFormat};
+
+/// how a sprite is turned towards the camera
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Billboard {
+    /// the sprite always faces the camera, for particles and markers
+    Spherical,
+    /// the sprite only turns around the axis, for trees standing on the ground
+    Cylindrical(Vector3<f32>),
+}
+
+impl Default for Billboard {
+    fn default() -> Self {
+        Billboard::Spherical
+    }
+}
+
+/// one quad of a sprite
+#[derive(Copy, Clone, Debug)]
+pub struct SpriteInstance {
+    /// the center of the quad
+    pub position: Vector3<f32>,
+    /// the width and height in world units, the height is along the axis of cylindrical sprites
+    pub size: Vector2<f32>,
+    /// multiplied with the color of the texture
+    pub tint: Vector4<f32>,
+    /// the part of the texture that is shown, x, y, width and height in uv coordinates
+    pub atlas_rect: [f32; 4],
+    pub billboard: Billboard,
+}
+
+impl SpriteInstance {
+    /// a spherical sprite showing the whole texture without a tint
+    pub fn new(position: Vector3<f32>, size: Vector2<f32>) -> Self {
+        Self {
+            position,
+            size,
+            tint: Vector4::new(1.0, 1.0, 1.0, 1.0),
+            atlas_rect: [0.0, 0.0, 1.0, 1.0],
+            billboard: Billboard::Spherical,
+        }
+    }
+    /// the quad can face any direction, so the bounds are a sphere around its corners
+    pub(crate) fn bounds(&self) -> Bounds {
+        let radius = self.size.magnitude() / 2.0;
+        let extents = Vector3::new(radius, radius, radius);
+        Bounds {
+            aabb: Aabb {
+                min: self.position - extents,
+                max: self.position + extents,
+            },
+            sphere: BoundingSphere {
+                center: self.position,
+                radius,
+            },
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct SpriteInstanceRaw {
+    position: [f32; 4],
+    /// w is 1 for cylindrical sprites and 0 for spherical ones
+    axis: [f32; 4],
+    tint: [f32; 4],
+    atlas_rect: [f32; 4],
+    size: [f32; 2],
+}
+
+impl From<&SpriteInstance> for SpriteInstanceRaw {
+    fn from(instance: &SpriteInstance) -> Self {
+        Self {
+            position: instance.position.extend(1.0).into(),
+            axis: match instance.billboard {
+                Billboard::Spherical => [0.0; 4],
+                Billboard::Cylindrical(axis) => axis.extend(1.0).into(),
+            },
+            tint: instance.tint.into(),
+            atlas_rect: instance.atlas_rect,
+            size: instance.size.into(),
+        }
+    }
+}
+
+impl SpriteInstanceRaw {
+    pub fn desc<'a>() -> VertexBufferLayout<'a> {
+        VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::InputStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    shader_location: 9,
+                    offset: 0,
+                    format: VertexFormat::Float4,
+                },
+                wgpu::VertexAttribute {
+                    shader_location: 10,
+                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    format: VertexFormat::Float4,
+                },
+                wgpu::VertexAttribute {
+                    shader_location: 11,
+                    offset: std::mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    format: VertexFormat::Float4,
+                },
+                wgpu::VertexAttribute {
+                    shader_location: 12,
+                    offset: std::mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+                    format: VertexFormat::Float4,
+                },
+                wgpu::VertexAttribute {
+                    shader_location: 13,
+                    offset: std::mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
+                    format: VertexFormat::Float2,
+                },
+            ],
+        }
+    }
+    /// the corners of the quad, the locations below 3 are taken by the vertices of the uv meshes
+    pub fn corner_desc<'a>() -> VertexBufferLayout<'a> {
+        VertexBufferLayout {
+            array_stride: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+            step_mode: wgpu::InputStepMode::Vertex,
+            attributes: &[wgpu::VertexAttribute {
+                shader_location: 3,
+                offset: 0,
+                format: VertexFormat::Float2,
+            }],
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub use instance::*;
+pub use sprite::*;
+
+mod instance;
+mod sprite;
@@ -0,0 +1,84 @@
+use crate::bounds::{update_instance_bounds, Bounds};
+use crate::growable_buffer::GrowableBuffer;
+use crate::sprites::{SpriteInstance, SpriteInstanceRaw};
+use crate::texture::Texture;
+use crate::uv_mesh::create_diffuse_bind_group;
+use crate::{texture, BlendMode, Instances, RenderState, RendererError, DEFAULT_LAYER};
+use std::path::Path;
+use wgpu::{BindGroup, BindGroupLayout, BufferUsage, Device, Queue};
+
+/// quads with the same texture that face the camera
+pub struct Sprite {
+    pub texture: Texture,
+    pub instances: Instances<SpriteInstance>,
+    pub instance_buffer: GrowableBuffer,
+    pub instances_in_buffer: usize,
+    pub bind_group: BindGroup,
+    pub bind_group_layout: BindGroupLayout,
+    /// the viewport layers the sprite is drawn in
+    pub layers: u32,
+    /// how the sprite is blended with what is behind it
+    pub blend_mode: BlendMode,
+    /// the bounds of every instance in the instance buffer, used to skip instances no camera sees
+    pub(crate) instance_bounds: Vec<Bounds>,
+}
+
+impl Sprite {
+    pub fn new<P: AsRef<Path>>(
+        device: &Device,
+        queue: &Queue,
+        path: P,
+    ) -> Result<Self, RendererError> {
+        let texture = Texture::load(
+            device,
+            queue,
+            path,
+            wgpu::FilterMode::Nearest,
+            wgpu::FilterMode::Nearest,
+        )?;
+        let bind_group_layout = texture::create_default_bind_group_layout(device);
+        let bind_group = create_diffuse_bind_group(device, &bind_group_layout, &texture);
+        Ok(Self {
+            texture,
+            instances: Instances::new(),
+            instance_buffer: GrowableBuffer::new(
+                device,
+                "sprite instance buffer",
+                BufferUsage::VERTEX,
+                &[],
+            ),
+            instances_in_buffer: 0,
+            bind_group,
+            bind_group_layout,
+            layers: DEFAULT_LAYER,
+            blend_mode: BlendMode::Opaque,
+            instance_bounds: vec![],
+        })
+    }
+    /// every sprite is a triangle strip that is seen from both sides
+    pub(crate) fn render_state() -> RenderState {
+        RenderState::double_sided().topology(wgpu::PrimitiveTopology::TriangleStrip)
+    }
+    /// write the instances that changed since the last update to the GPU
+    pub fn update(&mut self, device: &Device, queue: &Queue) {
+        let changed = self.instances.upload(
+            device,
+            queue,
+            &mut self.instance_buffer,
+            SpriteInstanceRaw::from,
+        );
+        self.instances_in_buffer = self.instances.len();
+        update_instance_bounds(
+            &mut self.instance_bounds,
+            &self.instances,
+            &changed,
+            SpriteInstance::bounds,
+        );
+    }
+    pub fn update_texture(&mut self, device: &Device) {
+        self.bind_group = create_diffuse_bind_group(device, &self.bind_group_layout, &self.texture);
+    }
+    pub fn is_empty(&self) -> bool {
+        self.instances_in_buffer == 0
+    }
+}
@@ -4,6 +4,7 @@ pub struct Texture {
     pub sampler: wgpu::Sampler,
     pub size: (u32, u32),
 }
+use crate::RendererError;
 use std::path::Path;
 use wgpu::{Device, Queue};
 
@@ -14,14 +15,17 @@ impl Texture {
         path: P,
         mag_filter: wgpu::FilterMode,
         min_filter: wgpu::FilterMode,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, RendererError> {
         // Needed to appease the borrow checker
         let path_copy = path.as_ref().to_path_buf();
         let label = path_copy.to_str();
         println!("{:?}", path.as_ref().to_str());
-        let img = match image::open(path) {
-            Ok(img) => img,
-            Err(_) => create_colored([255, 255, 255, 255]),
+        // materials without a texture point to a file that doesn't exist, those are white
+        // a file that exists but can't be decoded is an error
+        let img = if path.as_ref().is_file() {
+            image::open(path)?
+        } else {
+            create_colored([255, 255, 255, 255])
         };
 
         Ok(Self::from_image(
@@ -1,5 +1,5 @@
 use crate::instance::InstanceRaw;
-use cgmath::SquareMatrix;
+use cgmath::{ElementWise, SquareMatrix};
 use cgmath::{Matrix3, Matrix4, Vector3};
 
 #[derive(Copy, Clone, Debug)]
@@ -23,6 +23,10 @@ impl Transform {
             scale: Vector3::new(1.0, 1.0, 1.0),
         }
     }
+    /// move a point from the space of the mesh into the world
+    pub fn transform_point(&self, point: Vector3<f32>) -> Vector3<f32> {
+        self.position + self.rotation * point.mul_element_wise(self.scale)
+    }
 }
 
 impl std::ops::Mul for Transform {
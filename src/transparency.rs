@@ -0,0 +1,111 @@
+use crate::bounds::{Bounds, Frustum};
+use cgmath::{InnerSpace, Vector3};
+
+/// how the color of a mesh is combined with what was drawn before it
+/// everything but Opaque is drawn after the opaque meshes, sorted back to front and without writing depth
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// the alpha is ignored and the color replaces what is behind it
+    Opaque,
+    /// the color is mixed with what is behind it by its alpha
+    Alpha,
+    /// the color multiplied by its alpha is added to what is behind it, for glows and particles
+    Additive,
+    /// what is behind is multiplied by the color, the alpha is ignored, for tinted glass and shade
+    Multiply,
+}
+
+impl Default for BlendMode {
+    fn default() -> Self {
+        BlendMode::Opaque
+    }
+}
+
+impl BlendMode {
+    pub fn is_transparent(self) -> bool {
+        self != BlendMode::Opaque
+    }
+    /// the color target of a forward pipeline that blends like this
+    pub(crate) fn color_target(self, format: wgpu::TextureFormat) -> wgpu::ColorTargetState {
+        let blend = |src_factor, dst_factor| wgpu::BlendState {
+            src_factor,
+            dst_factor,
+            operation: wgpu::BlendOperation::Add,
+        };
+        let (color_blend, alpha_blend) = match self {
+            BlendMode::Opaque => (wgpu::BlendState::REPLACE, wgpu::BlendState::REPLACE),
+            BlendMode::Alpha => (
+                blend(
+                    wgpu::BlendFactor::SrcAlpha,
+                    wgpu::BlendFactor::OneMinusSrcAlpha,
+                ),
+                blend(wgpu::BlendFactor::One, wgpu::BlendFactor::OneMinusSrcAlpha),
+            ),
+            BlendMode::Additive => (
+                blend(wgpu::BlendFactor::SrcAlpha, wgpu::BlendFactor::One),
+                blend(wgpu::BlendFactor::Zero, wgpu::BlendFactor::One),
+            ),
+            BlendMode::Multiply => (
+                blend(wgpu::BlendFactor::DstColor, wgpu::BlendFactor::Zero),
+                blend(wgpu::BlendFactor::Zero, wgpu::BlendFactor::One),
+            ),
+        };
+        wgpu::ColorTargetState {
+            format,
+            color_blend,
+            alpha_blend,
+            write_mask: wgpu::ColorWrite::ALL,
+        }
+    }
+}
+
+/// one instance of a transparent mesh that has to be drawn on its own to be in the right order
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct TransparentInstance<T> {
+    pub mesh: T,
+    /// the position of the instance in the instance buffer of the mesh
+    pub instance: u32,
+    /// the squared distance from the camera to the center of the instance
+    pub distance: f32,
+}
+
+/// add the instances in the instance buffer of a mesh that the frustum contains
+/// with bounds for every instance in the buffer they are culled and sorted by the center of their bounds
+/// otherwise all instances are added and sorted by their positions
+pub(crate) fn push_transparent_instances<T: Copy>(
+    instances: &mut Vec<TransparentInstance<T>>,
+    mesh: T,
+    instance_bounds: &[Bounds],
+    positions: impl Iterator<Item = Vector3<f32>>,
+    instances_in_buffer: usize,
+    frustum: Option<&Frustum>,
+    eye: Vector3<f32>,
+) {
+    let mut push = |instance: usize, center: Vector3<f32>| {
+        instances.push(TransparentInstance {
+            mesh,
+            instance: instance as u32,
+            distance: (center - eye).magnitude2(),
+        })
+    };
+    if instance_bounds.len() == instances_in_buffer {
+        for (instance, bounds) in instance_bounds.iter().enumerate() {
+            if frustum.map_or(true, |frustum| frustum.intersects(bounds)) {
+                push(instance, bounds.sphere.center);
+            }
+        }
+    } else {
+        for (instance, position) in positions.take(instances_in_buffer).enumerate() {
+            push(instance, position);
+        }
+    }
+}
+
+/// the farthest instances are drawn first, so the closer ones are blended on top of them
+pub(crate) fn sort_back_to_front<T>(instances: &mut [TransparentInstance<T>]) {
+    instances.sort_by(|a, b| {
+        b.distance
+            .partial_cmp(&a.distance)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+}
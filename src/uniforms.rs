@@ -1,5 +1,7 @@
+use crate::Camera;
 use bytemuck::Pod;
 use bytemuck::Zeroable;
+use cgmath::InnerSpace;
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
@@ -10,6 +12,26 @@ pub struct GlobalUniforms {
     pub num_lights: [u32; 4],
     pub ambient_light: [f32; 4],
     pub lighting_enabled: u32,
+    pub _padding_2: [u32; 3],
+    /// the directions to the right and up on the screen, for quads that face the camera
+    pub camera_right: [f32; 4],
+    pub camera_up: [f32; 4],
+    /// the size of the view in pixels, only x and y are used
+    pub viewport_size: [f32; 4],
+}
+
+impl GlobalUniforms {
+    /// set everything that is different between the views
+    pub fn set_view(&mut self, camera: &Camera, size: (u32, u32)) {
+        let direction = camera.get_direction().normalize();
+        let right = direction.cross(camera.get_up()).normalize();
+        let up = right.cross(direction);
+        self.proj = camera.build_view_projection_matrix().into();
+        self.camera_pos = camera.get_position().into();
+        self.camera_right = right.extend(0.0).into();
+        self.camera_up = up.extend(0.0).into();
+        self.viewport_size = [size.0 as f32, size.1 as f32, 0.0, 0.0];
+    }
 }
 
 #[repr(C)]
@@ -17,4 +39,4 @@ pub struct GlobalUniforms {
 pub struct EntityUniforms {
     pub model: [[f32; 4]; 4],
     pub color: [f32; 4],
-}
\ No newline at end of file
+}
@@ -1,6 +1,7 @@
 #[cfg(feature = "hot_reload_shader")]
 use crate::render_passes::shader_reload::ShaderHotReload;
-use crate::WgpuRenderer;
+use crate::viewport::PixelRect;
+use crate::{Frustum, WgpuRenderer};
 use wgpu::util::DeviceExt;
 use crate::api::lights::{SimpleLightRaw, RealLightRaw};
 
@@ -13,14 +14,81 @@ impl Update for WgpuRenderer {
         #[cfg(feature = "hot_reload_shader")]
         self.update_pipelines();
 
-        self.global_uniforms.proj = self.camera.build_view_projection_matrix().into();
-        self.global_uniforms.camera_pos = self.camera.get_position().into();
+        let screen = (self.sc_desc.width, self.sc_desc.height);
+        let main_rect = PixelRect::from_normalized(self.main_viewport_rect, screen);
+        if let Some(rect) = main_rect {
+            self.camera.set_aspect_ratio(rect.aspect());
+        }
+        self.global_uniforms
+            .set_view(&self.camera, main_rect.map_or((0, 0), |rect| rect.size()));
         self.global_uniforms.num_lights = [
             self.real_lights.len() as u32,
             self.simple_lights.len() as u32,
             0,
             0,
         ];
+
+        // every view gets its own copy of the global uniforms
+        // they are copied into the uniform buffers of the forward passes right before the view is drawn
+        let mut view_uniforms = Vec::with_capacity(self.viewports.len() + 1);
+        view_uniforms.push(self.global_uniforms);
+        self.view_frustums.clear();
+        self.view_frustums
+            .push(Frustum::from_matrix(self.global_uniforms.proj.into()));
+        self.view_positions.clear();
+        self.view_positions.push(self.camera.get_position());
+        let viewports = &mut self.viewports;
+        for viewport in self
+            .viewport_order
+            .iter()
+            .filter_map(|&key| viewports.get_mut(key))
+        {
+            let rect = PixelRect::from_normalized(viewport.rect, screen);
+            if let Some(rect) = rect {
+                viewport.camera.set_aspect_ratio(rect.aspect());
+            }
+            let mut uniforms = self.global_uniforms;
+            uniforms.set_view(&viewport.camera, rect.map_or((0, 0), |rect| rect.size()));
+            view_uniforms.push(uniforms);
+            self.view_frustums
+                .push(Frustum::from_matrix(uniforms.proj.into()));
+            self.view_positions.push(viewport.camera.get_position());
+        }
+        let render_textures = &mut self.render_textures;
+        for render_texture in self
+            .render_texture_order
+            .iter()
+            .filter_map(|&key| render_textures.get_mut(key))
+        {
+            let aspect = render_texture.aspect();
+            render_texture.camera.set_aspect_ratio(aspect);
+            let mut uniforms = self.global_uniforms;
+            uniforms.set_view(&render_texture.camera, render_texture.size());
+            view_uniforms.push(uniforms);
+            self.view_frustums
+                .push(Frustum::from_matrix(uniforms.proj.into()));
+            self.view_positions
+                .push(render_texture.camera.get_position());
+        }
+        for window in self.windows.iter_mut() {
+            let aspect = window.aspect();
+            window.camera.set_aspect_ratio(aspect);
+            let mut uniforms = self.global_uniforms;
+            uniforms.set_view(&window.camera, window.size());
+            view_uniforms.push(uniforms);
+            self.view_frustums
+                .push(Frustum::from_matrix(uniforms.proj.into()));
+            self.view_positions.push(window.camera.get_position());
+        }
+        self.view_uniforms_buf = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("view uniforms"),
+                contents: bytemuck::cast_slice(&view_uniforms),
+                usage: wgpu::BufferUsage::COPY_SRC,
+            });
+        self.views_in_buffer = view_uniforms.len();
+
         self.simple_lights_storage_buffer =
             self.device
                 .create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -38,7 +106,7 @@ impl Update for WgpuRenderer {
         self.real_lights_storage_buffer =
             self.device
                 .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("simple lights storage buffer"),
+                    label: Some("real lights storage buffer"),
                     contents: bytemuck::cast_slice(
                         &self
                             .real_lights
@@ -99,7 +167,8 @@ impl Update for WgpuRenderer {
                 });
         }
 
-        if !self.uv_meshes.is_empty() {
+        // the sprites are drawn with the bind group of the uv meshes
+        if !self.uv_meshes.is_empty() || !self.sprites.is_empty() {
             self.queue.write_buffer(
                 &self.passes.uv_forward_pass.uniform_buf,
                 0,
@@ -214,5 +283,35 @@ impl Update for WgpuRenderer {
                 });
             self.lines.update(&self.device);
         }
+        if !self.point_clouds.is_empty() {
+            self.queue.write_buffer(
+                &self.passes.point_cloud_forward_pass.uniform_buf,
+                0,
+                bytemuck::cast_slice(&[self.global_uniforms]),
+            );
+            self.passes.point_cloud_forward_pass.bind_group =
+                self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &self.passes.point_cloud_forward_pass.bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::Buffer {
+                                buffer: &self.passes.point_cloud_forward_pass.uniform_buf,
+                                offset: 0,
+                                size: None,
+                            },
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Buffer {
+                                buffer: &self.simple_lights_storage_buffer,
+                                offset: 0,
+                                size: None,
+                            },
+                        },
+                    ],
+                    label: None,
+                });
+        }
     }
 }
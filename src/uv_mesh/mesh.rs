@@ -1,80 +1,93 @@
+use crate::bounds::{update_instance_bounds, Bounds};
+use crate::growable_buffer::{write_indices, GrowableBuffer};
+use crate::indices::DrawRange;
 use crate::instance::InstanceRaw;
+use crate::slots::SlotKey;
 use crate::texture::Texture;
 use crate::uv_mesh::vertex::UvVertex;
-use crate::{texture, Transform};
+use crate::{
+    texture, BlendMode, Indices, Instances, RenderState, RendererError, Transform, DEFAULT_LAYER,
+};
 use std::path::Path;
-use wgpu::util::{BufferInitDescriptor, DeviceExt};
-use wgpu::{BindGroup, BindGroupLayout, Buffer, BufferDescriptor, BufferUsage, Device, Queue};
+use wgpu::{BindGroup, BindGroupLayout, BufferUsage, Device, Queue};
 
 pub struct UvModel {
     pub vertices: Vec<UvVertex>,
-    pub indices: Option<Vec<u16>>,
+    pub indices: Option<Indices>,
     pub diffuse_texture: Texture,
 
-    pub instances: Vec<Transform>,
-    pub vertex_buffer: wgpu::Buffer,
-    pub index_buffer: Option<wgpu::Buffer>,
+    pub instances: Instances<Transform>,
+    pub vertex_buffer: GrowableBuffer,
+    pub index_buffer: Option<GrowableBuffer>,
+    pub vertex_count: usize,
     pub index_count: usize,
-    pub instance_buffer: Buffer,
+    /// the width of the indices in index_buffer
+    pub index_format: wgpu::IndexFormat,
+    pub instance_buffer: GrowableBuffer,
     pub instances_in_buffer: usize,
     pub diffuse_bind_group: BindGroup,
     pub diffuse_bind_group_layout: BindGroupLayout,
+    /// the viewport layers this mesh is drawn in
+    pub layers: u32,
+    /// how the mesh is blended with what is behind it
+    pub blend_mode: BlendMode,
+    /// how the triangles of the mesh are drawn
+    pub render_state: RenderState,
+    /// the render texture that is sampled instead of the diffuse texture
+    pub(crate) render_texture: Option<SlotKey>,
+    /// the box and sphere around the vertices, None if there are no vertices
+    pub bounds: Option<Bounds>,
+    /// the bounds of every instance in the instance buffer, used to skip instances no camera sees
+    pub(crate) instance_bounds: Vec<Bounds>,
 }
 
 impl UvModel {
     pub fn new<P: AsRef<Path>>(
         vertices: Vec<UvVertex>,
-        indices: Option<Vec<u16>>,
+        indices: Option<Indices>,
         device: &Device,
         queue: &Queue,
         path: P,
-    ) -> Self {
+    ) -> Result<Self, RendererError> {
         let diffuse_texture = Texture::load(
             device,
             queue,
             path,
             wgpu::FilterMode::Nearest,
             wgpu::FilterMode::Nearest,
-        )
-        .unwrap();
+        )?;
 
-        let instances = vec![];
-        let instance_buffer = device.create_buffer(&BufferDescriptor {
-            label: Some("model instance buffer"),
-            size: 0,
-            usage: BufferUsage::VERTEX,
-            mapped_at_creation: false,
-        });
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Cubes Vertex Buffer"),
-            contents: bytemuck::cast_slice(&vertices),
-            usage: BufferUsage::VERTEX,
+        let instances = Instances::new();
+        let instance_buffer =
+            GrowableBuffer::new(device, "instance vertex buffer", BufferUsage::VERTEX, &[]);
+        let vertex_buffer = GrowableBuffer::new(
+            device,
+            "uv mesh vertex buffer",
+            BufferUsage::VERTEX,
+            bytemuck::cast_slice(&vertices),
+        );
+        let index_buffer = indices.as_ref().map(|indices| {
+            GrowableBuffer::new(
+                device,
+                "uv mesh index buffer",
+                BufferUsage::INDEX,
+                indices.as_bytes(),
+            )
         });
-        let index_buffer = indices.as_ref().map(|indices| device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Cubes Index Buffer"),
-            contents: bytemuck::cast_slice(indices),
-            usage: BufferUsage::INDEX,
-        }));
 
         let diffuse_bind_group_layout = texture::create_default_bind_group_layout(&device);
-        let diffuse_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("diffuse bind group"),
-            layout: &diffuse_bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&diffuse_texture.view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&diffuse_texture.sampler),
-                },
-            ],
-        });
+        let diffuse_bind_group =
+            create_diffuse_bind_group(device, &diffuse_bind_group_layout, &diffuse_texture);
 
-        Self {
+        Ok(Self {
+            bounds: Bounds::from_points(vertices.iter().map(UvVertex::get_position)),
+            instance_bounds: vec![],
+            vertex_count: vertices.len(),
             vertices,
-            index_count: if let Some(indices) = &indices { indices.len() } else { 0 },
+            index_count: indices.as_ref().map_or(0, Indices::len),
+            index_format: indices
+                .as_ref()
+                .map_or(wgpu::IndexFormat::Uint16, Indices::format),
             indices,
             diffuse_texture,
             instances,
@@ -84,39 +97,99 @@ impl UvModel {
             diffuse_bind_group,
             diffuse_bind_group_layout,
             instances_in_buffer: 0,
-        }
+            layers: DEFAULT_LAYER,
+            blend_mode: BlendMode::Opaque,
+            render_state: RenderState::default(),
+            render_texture: None,
+        })
     }
-    pub fn update(&mut self, device: &Device) {
-        self.instance_buffer = device.create_buffer_init(&BufferInitDescriptor {
-            label: Some("instance vertex buffer"),
-            contents: bytemuck::cast_slice(
-                &self
-                    .instances
-                    .iter()
-                    .map(|transform: &Transform| transform.into())
-                    .collect::<Vec<InstanceRaw>>(),
-            ),
-            usage: BufferUsage::VERTEX,
-        });
+    /// write the instances that changed since the last update to the GPU
+    pub fn update(&mut self, device: &Device, queue: &Queue) {
+        let changed =
+            self.instances
+                .upload(device, queue, &mut self.instance_buffer, InstanceRaw::from);
         self.instances_in_buffer = self.instances.len();
+        match &self.bounds {
+            Some(bounds) => update_instance_bounds(
+                &mut self.instance_bounds,
+                &self.instances,
+                &changed,
+                |transform| bounds.transformed(transform),
+            ),
+            None => self.instance_bounds.clear(),
+        }
     }
     pub fn update_texture(&mut self, device: &Device) {
-        self.diffuse_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("diffuse bind group"),
-            layout: &self.diffuse_bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&self.diffuse_texture.view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&self.diffuse_texture.sampler),
-                },
-            ],
-        });
+        self.diffuse_bind_group = create_diffuse_bind_group(
+            device,
+            &self.diffuse_bind_group_layout,
+            &self.diffuse_texture,
+        );
+    }
+    /// sample the given texture instead of the diffuse texture
+    /// update_texture switches back to the diffuse texture
+    pub fn bind_texture(&mut self, device: &Device, texture: &Texture) {
+        self.diffuse_bind_group =
+            create_diffuse_bind_group(device, &self.diffuse_bind_group_layout, texture);
+    }
+    /// replace the vertices and indices, the buffers are reused if the new geometry fits into them
+    pub fn update_geometry(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        vertices: &[UvVertex],
+        indices: Option<&Indices>,
+    ) {
+        self.vertex_buffer
+            .write(device, queue, bytemuck::cast_slice(vertices));
+        self.vertex_count = vertices.len();
+        self.bounds = Bounds::from_points(vertices.iter().map(UvVertex::get_position));
+        // the instances are not culled until the next update
+        self.instance_bounds.clear();
+        write_indices(
+            device,
+            queue,
+            &mut self.index_buffer,
+            "uv mesh index buffer",
+            indices,
+        );
+        self.index_count = indices.map_or(0, Indices::len);
+        self.index_format = indices.map_or(wgpu::IndexFormat::Uint16, Indices::format);
+        self.vertices = vertices.to_vec();
+        self.indices = indices.cloned();
+    }
+    /// the format of the indices, None if the mesh is drawn without indices
+    pub(crate) fn indices_format(&self) -> Option<wgpu::IndexFormat> {
+        self.index_buffer.as_ref().map(|_| self.index_format)
+    }
+    pub(crate) fn draw_range(&self) -> DrawRange {
+        DrawRange::new(
+            self.vertex_count,
+            self.index_buffer.as_ref().map(|_| self.index_count),
+        )
     }
     pub fn is_empty(&self) -> bool {
         self.instances_in_buffer == 0
     }
 }
+
+pub(crate) fn create_diffuse_bind_group(
+    device: &Device,
+    layout: &BindGroupLayout,
+    texture: &Texture,
+) -> BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("diffuse bind group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&texture.view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(&texture.sampler),
+            },
+        ],
+    })
+}
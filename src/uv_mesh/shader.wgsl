@@ -11,6 +11,19 @@ var<in> model_matrix_2: vec4<f32>;
 [[location(8)]]
 var<in> model_matrix_3: vec4<f32>;
 
+// the corner of the quad and the instance of a sprite
+[[location(3)]] var<in> in_corner: vec2<f32>;
+[[location(9)]]
+var<in> sprite_position: vec4<f32>;
+[[location(10)]]
+var<in> sprite_axis: vec4<f32>;
+[[location(11)]]
+var<in> sprite_tint: vec4<f32>;
+[[location(12)]]
+var<in> sprite_atlas_rect: vec4<f32>;
+[[location(13)]]
+var<in> sprite_size: vec2<f32>;
+
 [[builtin(position)]]
 var<out> out_position: vec4<f32>;
 
@@ -21,6 +34,12 @@ struct Globals {
     num_lights: vec4<u32>;
     ambient_color: vec4<f32>;
     enable_lighting: u32;
+    padding_0: u32;
+    padding_1: u32;
+    padding_2: u32;
+    camera_right: vec4<f32>;
+    camera_up: vec4<f32>;
+    viewport_size: vec4<f32>;
 };
 
 [[group(0), binding(0)]]
@@ -39,6 +58,8 @@ var<out> out_normal_vs: vec3<f32>;
 var<out> out_position_vs: vec4<f32>;
 [[location(2)]]
 var<out> out_uv_vs: vec2<f32>;
+[[location(3)]]
+var<out> out_tint_vs: vec4<f32>;
 
 [[stage(vertex)]]
 fn vs_main() {
@@ -48,6 +69,25 @@ fn vs_main() {
     out_position_vs = model_matrix * in_position;
     out_position = u_globals.view_proj * out_position_vs;
     out_uv_vs = in_uv;
+    out_tint_vs = vec4<f32>(1.0, 1.0, 1.0, 1.0);
+}
+
+// a quad facing the camera, the axis of a cylindrical sprite is in w = 1
+[[stage(vertex)]]
+fn vs_sprite() {
+    var right: vec3<f32> = u_globals.camera_right.xyz;
+    var up: vec3<f32> = u_globals.camera_up.xyz;
+    if (sprite_axis.w != 0.0) {
+        up = normalize(sprite_axis.xyz);
+        right = normalize(cross(up, u_globals.camera_pos.xyz - sprite_position.xyz));
+    }
+    out_position_vs = vec4<f32>(sprite_position.xyz + right * (in_corner.x * sprite_size.x) + up * (in_corner.y * sprite_size.y), 1.0);
+    out_position = u_globals.view_proj * out_position_vs;
+    // the front of the quad points towards the camera
+    out_normal_vs = cross(right, up);
+    // v points down in the texture
+    out_uv_vs = sprite_atlas_rect.xy + vec2<f32>(in_corner.x + 0.5, 0.5 - in_corner.y) * sprite_atlas_rect.zw;
+    out_tint_vs = sprite_tint;
 }
 
 // fragment shader
@@ -120,6 +160,8 @@ var<in> in_normal_fs: vec3<f32>;
 var<in> in_position_fs: vec4<f32>;
 [[location(2)]]
 var<in> uv_in_fs: vec2<f32>;
+[[location(3)]]
+var<in> tint_in_fs: vec4<f32>;
 
 [[location(0)]]
 var<out> out_color_fs: vec4<f32>;
@@ -133,7 +175,7 @@ var sampler_diffuse: sampler;
 
 [[stage(fragment)]]
 fn fs_main() {
-    const object_color: vec4<f32> = textureSample(t_diffuse, sampler_diffuse, uv_in_fs);
+    const object_color: vec4<f32> = textureSample(t_diffuse, sampler_diffuse, uv_in_fs) * tint_in_fs;
 
     if (u_globals.enable_lighting != 0) {
         const view_dir: vec3<f32> = normalize(u_globals.camera_pos.xyz - in_position_fs.xyz);
@@ -224,8 +266,8 @@ fn fs_main() {
             }
         }
 
-        // multiply the light by material color
-        out_color_fs = color * object_color;
+        // multiply the light by material color, the light does not change how transparent it is
+        out_color_fs = vec4<f32>(color.xyz * object_color.xyz, object_color.w);
     } else {
         out_color_fs = object_color;
     }
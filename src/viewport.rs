@@ -0,0 +1,82 @@
+use crate::slots::SlotKey;
+use crate::Camera;
+
+/// the layer every mesh and model is in when it is loaded
+pub const DEFAULT_LAYER: u32 = 1;
+/// a layer mask that includes every layer
+pub const ALL_LAYERS: u32 = u32::MAX;
+
+/// a region of the screen the scene is drawn into with its own camera
+#[derive(Copy, Clone)]
+pub struct Viewport {
+    pub camera: Camera,
+    /// x, y, width and height as fractions of the screen, (0, 0) is the top left corner
+    pub rect: [f32; 4],
+    /// only meshes and models that are in at least one of these layers are drawn
+    pub layer_mask: u32,
+}
+
+impl Viewport {
+    /// a viewport that draws all layers
+    /// the aspect ratio of the camera is set by the renderer to match the rect
+    pub fn new(camera: Camera, rect: [f32; 4]) -> Self {
+        Self {
+            camera,
+            rect,
+            layer_mask: ALL_LAYERS,
+        }
+    }
+    pub fn with_layer_mask(mut self, layer_mask: u32) -> Self {
+        self.layer_mask = layer_mask;
+        self
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ViewportHandle {
+    key: SlotKey,
+}
+impl ViewportHandle {
+    pub(crate) fn new(key: SlotKey) -> Self {
+        Self { key }
+    }
+    pub(crate) fn key(&self) -> SlotKey {
+        self.key
+    }
+}
+
+/// the part of a target a viewport covers, in pixels
+/// it is clipped to the target so it can be used as a scissor rect
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl PixelRect {
+    /// None if the rect doesn't cover a single pixel of the target
+    pub fn from_normalized(rect: [f32; 4], size: (u32, u32)) -> Option<Self> {
+        let (target_width, target_height) = (size.0 as f32, size.1 as f32);
+        let x0 = (rect[0] * target_width).round().max(0.0).min(target_width);
+        let y0 = (rect[1] * target_height).round().max(0.0).min(target_height);
+        let x1 = ((rect[0] + rect[2]) * target_width).round().max(0.0).min(target_width);
+        let y1 = ((rect[1] + rect[3]) * target_height).round().max(0.0).min(target_height);
+        if x1 <= x0 || y1 <= y0 {
+            return None;
+        }
+        Some(Self {
+            x: x0 as u32,
+            y: y0 as u32,
+            width: (x1 - x0) as u32,
+            height: (y1 - y0) as u32,
+        })
+    }
+    pub fn aspect(&self) -> f32 {
+        self.width as f32 / self.height as f32
+    }
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+}
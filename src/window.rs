@@ -0,0 +1,81 @@
+use crate::render_target::{ForwardTextures, RenderTarget};
+use crate::shapes::Shape;
+use crate::slots::SlotKey;
+use crate::text::Paragraph;
+use crate::{Camera, ALL_LAYERS};
+use wgpu::{Device, SwapChainDescriptor};
+
+/// an extra window that shows the scene of the renderer
+/// it shares the device, the meshes, models and lights with the main window
+/// but has its own swap chain, camera, paragraphs and shapes
+pub struct ExtraWindow {
+    pub camera: Camera,
+    /// only meshes and models that are in at least one of these layers are drawn
+    pub layer_mask: u32,
+    /// the paragraphs displayed in this window
+    pub paragraphs: Vec<Paragraph>,
+    /// the 2D shapes displayed in this window
+    pub shapes: Vec<Shape>,
+    pub(crate) sc_desc: SwapChainDescriptor,
+    pub(crate) target: RenderTarget,
+    /// None until the window has an area
+    pub(crate) textures: Option<ForwardTextures>,
+}
+
+impl ExtraWindow {
+    pub(crate) fn new(
+        device: &Device,
+        camera: Camera,
+        surface: wgpu::Surface,
+        sc_desc: SwapChainDescriptor,
+        sample_count: u32,
+    ) -> Self {
+        let target = RenderTarget::window(device, surface, &sc_desc);
+        Self {
+            camera,
+            layer_mask: ALL_LAYERS,
+            paragraphs: vec![],
+            shapes: vec![],
+            textures: ForwardTextures::new(device, &sc_desc, sample_count),
+            sc_desc,
+            target,
+        }
+    }
+    /// recreate the swap chain and the textures of the forward pass with a new size
+    /// nothing is recreated while the window has no area
+    pub(crate) fn resize(&mut self, device: &Device, size: (u32, u32), sample_count: u32) {
+        self.sc_desc.width = size.0;
+        self.sc_desc.height = size.1;
+        if size.0 == 0 || size.1 == 0 {
+            return;
+        }
+        self.textures = ForwardTextures::new(device, &self.sc_desc, sample_count);
+        self.target.rebuild(device, &self.sc_desc);
+    }
+    /// the size of the window in pixels
+    pub fn size(&self) -> (u32, u32) {
+        (self.sc_desc.width, self.sc_desc.height)
+    }
+    /// get the aspect ratio of the window
+    /// this is 1.0 while the window has no area
+    pub fn aspect(&self) -> f32 {
+        if self.sc_desc.width == 0 || self.sc_desc.height == 0 {
+            1.0
+        } else {
+            self.sc_desc.width as f32 / self.sc_desc.height as f32
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WindowHandle {
+    key: SlotKey,
+}
+impl WindowHandle {
+    pub(crate) fn new(key: SlotKey) -> Self {
+        Self { key }
+    }
+    pub(crate) fn key(&self) -> SlotKey {
+        self.key
+    }
+}
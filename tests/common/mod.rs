@@ -0,0 +1,17 @@
+use finger_paint_wgpu::{Capture, RendererConfig, WgpuRenderer};
+
+pub const SIZE: (u32, u32) = (64, 64);
+
+/// a headless renderer with an empty scene
+/// the tests that use it need a GPU, so they are ignored unless run with --ignored
+pub fn headless() -> WgpuRenderer {
+    WgpuRenderer::new_headless(SIZE, RendererConfig::default())
+        .unwrap_or_else(|e| panic!("a GPU is needed to run this test: {}", e))
+}
+
+/// draw a frame and read it back
+pub fn capture(renderer: &mut WgpuRenderer) -> image::RgbaImage {
+    renderer.update();
+    renderer.render().unwrap();
+    renderer.capture_frame().unwrap()
+}
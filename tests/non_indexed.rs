@@ -0,0 +1,49 @@
+use finger_paint_wgpu::cgmath::{Point3, Vector3, Vector4};
+use finger_paint_wgpu::{ColorMeshInstance, ColorVertex, Lighting, MeshApi, Transform};
+
+mod common;
+
+use common::{capture, headless, SIZE};
+
+/// a triangle facing the camera that covers the center of the screen
+fn color_triangle() -> Vec<ColorVertex> {
+    let normal = Vector3::new(0.0, 0.0, 1.0);
+    let red = Vector4::new(1.0, 0.0, 0.0, 1.0);
+    [
+        Vector3::new(-1.0, -1.0, 0.0),
+        Vector3::new(1.0, -1.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+    ]
+    .iter()
+    .map(|position| ColorVertex::new(*position, normal, red))
+    .collect()
+}
+
+/// which range of a mesh is drawn is tested without a GPU next to Indices
+/// this only checks that a mesh without indices ends up on the screen
+#[test]
+#[ignore = "needs a GPU"]
+fn color_mesh_without_indices() {
+    let mut renderer = headless();
+    renderer.enable_lighting(false);
+    renderer.camera().set_position(Point3::new(0.0, 0.0, 3.0));
+    renderer
+        .camera()
+        .set_direction(Vector3::new(0.0, 0.0, -1.0));
+    let mesh = renderer.load_color_mesh(color_triangle(), None);
+    renderer
+        .color_mesh_instances(&mesh)
+        .unwrap()
+        .push(ColorMeshInstance {
+            transform: Transform::new(),
+            lighting: Lighting {
+                specular_strength: 0.0,
+                specular_spread: 1.0,
+                diffuse_strength: 1.0,
+            },
+        });
+    renderer.update_color_mesh(&mesh);
+    let frame = capture(&mut renderer);
+    assert_eq!(frame.get_pixel(SIZE.0 / 2, SIZE.1 / 2).0, [255, 0, 0, 255]);
+    assert_eq!(frame.get_pixel(0, 0).0, [0, 0, 0, 255]);
+}
@@ -0,0 +1,77 @@
+use finger_paint_wgpu::cgmath::Vector2;
+use finger_paint_wgpu::{Shape, ShapeKind};
+
+mod common;
+
+use common::{capture, headless};
+
+const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+fn rect(x: f32, y: f32, width: f32, height: f32) -> ShapeKind {
+    ShapeKind::Rect {
+        position: Vector2::new(x, y),
+        size: Vector2::new(width, height),
+    }
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+fn filled_rect_covers_its_pixels() {
+    let mut renderer = headless();
+    renderer
+        .shapes()
+        .push(Shape::filled(rect(16.0, 8.0, 32.0, 16.0), RED));
+    let frame = capture(&mut renderer);
+    assert_eq!(frame.get_pixel(32, 16).0, [255, 0, 0, 255]);
+    assert_eq!(frame.get_pixel(32, 40).0, [0, 0, 0, 255]);
+    assert_eq!(frame.get_pixel(8, 16).0, [0, 0, 0, 255]);
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+fn higher_z_is_drawn_on_top() {
+    let mut renderer = headless();
+    let shapes = renderer.shapes();
+    shapes.push(Shape::filled(rect(8.0, 8.0, 48.0, 48.0), BLUE).with_z(1));
+    shapes.push(Shape::filled(rect(0.0, 0.0, 64.0, 64.0), RED));
+    let frame = capture(&mut renderer);
+    assert_eq!(frame.get_pixel(32, 32).0, [0, 0, 255, 255]);
+    assert_eq!(frame.get_pixel(2, 2).0, [255, 0, 0, 255]);
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+fn stroked_circle_leaves_its_center_empty() {
+    let mut renderer = headless();
+    let circle = ShapeKind::Circle {
+        center: Vector2::new(32.0, 32.0),
+        radius: 20.0,
+    };
+    renderer.shapes().push(Shape::stroked(circle, 4.0, RED));
+    let frame = capture(&mut renderer);
+    assert_eq!(frame.get_pixel(32, 32).0, [0, 0, 0, 255]);
+    assert_eq!(frame.get_pixel(52, 32).0, [255, 0, 0, 255]);
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+fn concave_polygon_leaves_its_notch_empty() {
+    let mut renderer = headless();
+    // an L shape, the top right quarter of the square is cut out
+    let corners = vec![
+        Vector2::new(8.0, 8.0),
+        Vector2::new(32.0, 8.0),
+        Vector2::new(32.0, 32.0),
+        Vector2::new(56.0, 32.0),
+        Vector2::new(56.0, 56.0),
+        Vector2::new(8.0, 56.0),
+    ];
+    renderer
+        .shapes()
+        .push(Shape::filled(ShapeKind::Polygon(corners), RED));
+    let frame = capture(&mut renderer);
+    assert_eq!(frame.get_pixel(44, 20).0, [0, 0, 0, 255]);
+    assert_eq!(frame.get_pixel(20, 20).0, [255, 0, 0, 255]);
+    assert_eq!(frame.get_pixel(44, 44).0, [255, 0, 0, 255]);
+}